@@ -43,6 +43,7 @@ async fn app(
             fragment_shader: r#" void main() {
             gl_FragColor = color;
         }"#,
+            ..Default::default()
         },
     )?;
 
@@ -71,6 +71,7 @@ async fn app(
             fragment_shader: r#" void main() {
             gl_FragColor = frag_color;
         }"#,
+            ..Default::default()
         },
     )?;
 
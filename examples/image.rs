@@ -1,8 +1,7 @@
 use blinds::*;
 use golem::{
     Attribute, AttributeType, ColorFormat, Context, Dimension::D2, ElementBuffer, GeometryMode,
-    GolemError, ShaderDescription, ShaderProgram, Texture, Uniform, UniformType, UniformValue,
-    VertexBuffer,
+    GolemError, ShaderDescription, ShaderProgram, Texture, Uniform, UniformType, VertexBuffer,
 };
 
 async fn app(
@@ -55,6 +54,7 @@ async fn app(
             fragment_shader: r#" void main() {
             gl_FragColor = texture(image, frag_uv);
         }"#,
+            ..Default::default()
         },
     )?;
 
@@ -63,10 +63,7 @@ async fn app(
     vb.set_data(&vertices);
     eb.set_data(&indices);
     shader.bind();
-    shader.set_uniform("image", UniformValue::Int(1))?;
-
-    let bind_point = std::num::NonZeroU32::new(1).unwrap();
-    texture.set_active(bind_point);
+    shader.bind_texture("image", &texture)?;
 
     ctx.clear();
     unsafe {
@@ -0,0 +1,91 @@
+use blinds::*;
+use golem::{
+    Attribute, AttributeType, Context,
+    Dimension::{D2, D4},
+    ElementBuffer, GeometryMode, GolemError, ShaderDescription, ShaderProgram, VertexBuffer,
+};
+
+async fn app(
+    window: Window,
+    mut events: EventStream,
+) -> Result<(), GolemError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let ctx = unsafe {
+        &Context::from_loader_function_cstr(|func| window.get_proc_address(func))?
+    };
+    #[cfg(target_arch = "wasm32")]
+    let ctx = &Context::from_webgl2_context(window.webgl2_context())?;
+
+    #[rustfmt::skip]
+    let vertices = [
+        // Position
+        -0.1, -0.1,
+        0.1, -0.1,
+        0.0, 0.1,
+    ];
+    let indices = [0, 1, 2];
+
+    // One offset and color for each copy of the triangle
+    let mut instances = Vec::new();
+    for x in 0..5 {
+        for y in 0..5 {
+            let offset = [x as f32 * 0.4 - 0.8, y as f32 * 0.4 - 0.8];
+            let color = [x as f32 / 4.0, y as f32 / 4.0, 1.0, 1.0];
+            instances.extend_from_slice(&offset);
+            instances.extend_from_slice(&color);
+        }
+    }
+    let instance_count = 25;
+
+    let mut shader = ShaderProgram::new(
+        ctx,
+        ShaderDescription {
+            vertex_input: &[Attribute::new("vert_position", AttributeType::Vector(D2))],
+            instance_input: &[
+                Attribute::new("instance_offset", AttributeType::Vector(D2)),
+                Attribute::new("instance_color", AttributeType::Vector(D4)),
+            ],
+            fragment_input: &[Attribute::new("frag_color", AttributeType::Vector(D4))],
+            uniforms: &[],
+            vertex_shader: r#" void main() {
+            gl_Position = vec4(vert_position + instance_offset, 0, 1);
+            frag_color = instance_color;
+        }"#,
+            fragment_shader: r#" void main() {
+            gl_FragColor = frag_color;
+        }"#,
+            ..Default::default()
+        },
+    )?;
+
+    let mut vb = VertexBuffer::new(ctx)?;
+    let mut ib = VertexBuffer::new(ctx)?;
+    let mut eb = ElementBuffer::new(ctx)?;
+    vb.set_data(&vertices);
+    ib.set_data(&instances);
+    eb.set_data(&indices);
+    shader.bind();
+
+    ctx.clear();
+    unsafe {
+        shader.draw_instanced(
+            &vb,
+            &ib,
+            &eb,
+            0..indices.len(),
+            instance_count,
+            GeometryMode::Triangles,
+        )?;
+    }
+    window.present();
+
+    loop {
+        events.next_event().await;
+    }
+}
+
+fn main() {
+    run(Settings::default(), |window, events| async move {
+        app(window, events).await.unwrap()
+    });
+}
@@ -0,0 +1,105 @@
+use blinds::*;
+use golem::stencil::*;
+use golem::{
+    Attribute, AttributeType, Context,
+    Dimension::{D2, D4},
+    ElementBuffer, GeometryMode, GolemError, NumberType, ShaderDescription, ShaderProgram, Uniform,
+    UniformType, UniformValue, VertexBuffer,
+};
+
+async fn app(
+    window: Window,
+    mut events: EventStream,
+) -> Result<(), GolemError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let ctx = unsafe {
+        &Context::from_loader_function_cstr(|func| window.get_proc_address(func))?
+    };
+    #[cfg(target_arch = "wasm32")]
+    let ctx = &Context::from_webgl2_context(window.webgl2_context())?;
+
+    #[rustfmt::skip]
+    let triangle = [
+        -0.3, -0.3,
+        0.3, -0.3,
+        0.0, 0.3,
+    ];
+    #[rustfmt::skip]
+    let square = [
+        -0.6, -0.6,
+        0.6, -0.6,
+        0.6, 0.6,
+        -0.6, 0.6,
+    ];
+    let triangle_indices = [0, 1, 2];
+    let square_indices = [0, 1, 2, 2, 3, 0];
+
+    let mut shader = ShaderProgram::new(
+        ctx,
+        ShaderDescription {
+            vertex_input: &[Attribute::new("vert_position", AttributeType::Vector(D2))],
+            fragment_input: &[],
+            uniforms: &[Uniform::new(
+                "color",
+                UniformType::Vector(NumberType::Float, D4),
+            )],
+            vertex_shader: r#" void main() {
+                gl_Position = vec4(vert_position, 0, 1);
+            }"#,
+            fragment_shader: r#" void main() {
+                gl_FragColor = color;
+            }"#,
+            ..Default::default()
+        },
+    )?;
+
+    let mut vb = VertexBuffer::new(ctx)?;
+    let mut eb = ElementBuffer::new(ctx)?;
+    ctx.set_clear_stencil(0);
+    ctx.clear();
+    shader.bind();
+
+    // Draw the (red) triangle, writing 1 to the stencil buffer everywhere it covers
+    let write_one = StencilOperations {
+        pass: StencilOperation::Replace,
+        ..StencilOperations::default()
+    };
+    ctx.set_stencil_mode(Some(StencilTestMode {
+        function: StencilFunction::Always,
+        reference: 1,
+        front: write_one,
+        back: write_one,
+        ..StencilTestMode::default()
+    }));
+    vb.set_data(&triangle);
+    eb.set_data(&triangle_indices);
+    shader.set_uniform("color", UniformValue::Vector4([1.0, 0.0, 0.0, 1.0]))?;
+    unsafe {
+        shader.draw(&vb, &eb, 0..triangle_indices.len(), GeometryMode::Triangles)?;
+    }
+
+    // Draw the (green) square only where the triangle wasn't, leaving a triangle-shaped hole
+    ctx.set_stencil_mode(Some(StencilTestMode {
+        function: StencilFunction::NotEqual,
+        reference: 1,
+        ..StencilTestMode::default()
+    }));
+    vb.set_data(&square);
+    eb.set_data(&square_indices);
+    shader.set_uniform("color", UniformValue::Vector4([0.0, 1.0, 0.0, 1.0]))?;
+    unsafe {
+        shader.draw(&vb, &eb, 0..square_indices.len(), GeometryMode::Triangles)?;
+    }
+    ctx.set_stencil_mode(None);
+    window.present();
+
+    loop {
+        events.next_event().await;
+    }
+}
+
+fn main() {
+    run(Settings::default(), |window, events| async move {
+        app(window, events).await.unwrap()
+    });
+}
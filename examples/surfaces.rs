@@ -43,6 +43,7 @@ async fn app(
             fragment_shader: r#" void main() {
             gl_FragColor = frag_color;
         }"#,
+            ..Default::default()
         },
     )?;
 
@@ -98,6 +99,7 @@ async fn app(
             fragment_shader: r#" void main() {
             gl_FragColor = texture(image, frag_uv);
         }"#,
+            ..Default::default()
         },
     )?;
     vb.set_data(&vertices);
@@ -22,20 +22,119 @@ pub enum AttributeType {
     Vector(Dimension),
     /// A 2D array of f32 values, ranging from 2x2 to 4x4
     Matrix(Dimension, Dimension),
+    /// A single, scalar value stored in memory as the given component type
+    ///
+    /// `ScalarOf(ComponentType::F32)` is the same as `Scalar`
+    ScalarOf(ComponentType),
+    /// A vector value, ranging from 2 components to 4, stored in memory as the given component
+    /// type
+    ///
+    /// `VectorOf(ComponentType::F32, n)` is the same as `Vector(n)`
+    VectorOf(ComponentType, Dimension),
 }
 
-impl Position {
-    #[cfg(target_arch = "wasm32")]
-    fn glsl_string(self) -> &'static str {
-        use Position::*;
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+/// How each component of an attribute is stored in a vertex buffer
+///
+/// Normalized components are converted to floats when read by the shader, mapping the full range
+/// of the type to `0.0..=1.0` for unsigned types and `-1.0..=1.0` for signed types. Components
+/// that are not normalized are read as integers (`int`/`ivecn` or `uint`/`uvecn` in GLSL).
+pub enum ComponentType {
+    /// A 32-bit float
+    F32,
+    /// A 16-bit (half-precision) float, read as a float
+    ///
+    /// Rust has no stable `f16` type, so store the raw bits in a `u16`
+    F16,
+    /// An 8-bit unsigned integer
+    U8 { normalized: bool },
+    /// An 8-bit signed integer
+    I8 { normalized: bool },
+    /// A 16-bit unsigned integer
+    U16 { normalized: bool },
+    /// A 16-bit signed integer
+    I16 { normalized: bool },
+    /// A 32-bit unsigned integer, read as an integer
+    U32,
+    /// A 32-bit signed integer, read as an integer
+    I32,
+}
+
+impl ComponentType {
+    /// The size of a single component in bytes
+    pub fn size(self) -> i32 {
+        use ComponentType::*;
+
+        match self {
+            F32 | U32 | I32 => 4,
+            F16 | U16 { .. } | I16 { .. } => 2,
+            U8 { .. } | I8 { .. } => 1,
+        }
+    }
+
+    /// If the shader reads this component as an integer, rather than a float
+    pub fn is_integer(self) -> bool {
+        use ComponentType::*;
+
+        match self {
+            F32 | F16 => false,
+            U8 { normalized } | I8 { normalized } | U16 { normalized } | I16 { normalized } => {
+                !normalized
+            }
+            U32 | I32 => true,
+        }
+    }
+
+    pub(crate) fn is_normalized(self) -> bool {
+        use ComponentType::*;
+
+        match self {
+            U8 { normalized } | I8 { normalized } | U16 { normalized } | I16 { normalized } => {
+                normalized
+            }
+            F32 | F16 | U32 | I32 => false,
+        }
+    }
+
+    pub(crate) fn to_gl(self) -> u32 {
+        use ComponentType::*;
 
         match self {
-            Input => "attribute ",
-            Output => "varying ",
+            F32 => glow::FLOAT,
+            F16 => glow::HALF_FLOAT,
+            U8 { .. } => glow::UNSIGNED_BYTE,
+            I8 { .. } => glow::BYTE,
+            U16 { .. } => glow::UNSIGNED_SHORT,
+            I16 { .. } => glow::SHORT,
+            U32 => glow::UNSIGNED_INT,
+            I32 => glow::INT,
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
+    fn is_signed(self) -> bool {
+        use ComponentType::*;
+
+        matches!(self, F32 | F16 | I8 { .. } | I16 { .. } | I32)
+    }
+
+    fn glsl_prefix(self) -> &'static str {
+        match (self.is_integer(), self.is_signed()) {
+            (false, _) => "",
+            (true, true) => "i",
+            (true, false) => "u",
+        }
+    }
+
+    fn glsl_scalar(self) -> &'static str {
+        match (self.is_integer(), self.is_signed()) {
+            (false, _) => "float ",
+            (true, true) => "int ",
+            (true, false) => "uint ",
+        }
+    }
+}
+
+impl Position {
     fn glsl_string(self) -> &'static str {
         use Position::*;
 
@@ -55,27 +154,51 @@ impl Attribute {
         &self.name
     }
 
+    /// The number of components in the attribute
     pub(crate) fn size(&self) -> i32 {
         use AttributeType::*;
 
         match self.value {
-            Scalar => 1,
-            Vector(n) => n as i32,
+            Scalar | ScalarOf(_) => 1,
+            Vector(n) | VectorOf(_, n) => n as i32,
             Matrix(m, n) => (m as i32) * (n as i32),
         }
     }
 
-    pub(crate) fn as_glsl(&self, _is_vertex: bool, pos: Position, shader: &mut String) {
+    pub(crate) fn component_type(&self) -> ComponentType {
         use AttributeType::*;
 
-        #[cfg(target_arch = "wasm32")]
-        let pos = if _is_vertex { pos } else { Position::Output };
+        match self.value {
+            Scalar | Vector(_) | Matrix(_, _) => ComponentType::F32,
+            ScalarOf(component) | VectorOf(component, _) => component,
+        }
+    }
+
+    /// The size of the attribute in bytes
+    pub(crate) fn byte_size(&self) -> i32 {
+        self.size() * self.component_type().size()
+    }
 
+    /// The alignment of the attribute in bytes, following the rules of a `#[repr(C)]` struct
+    pub(crate) fn alignment(&self) -> i32 {
+        self.component_type().size()
+    }
+
+    pub(crate) fn as_glsl(&self, is_vertex: bool, pos: Position, shader: &mut String) {
+        use AttributeType::*;
+
+        // Integers can't be interpolated between the vertex and fragment stages
+        let between_stages = is_vertex == matches!(pos, Position::Output);
+        if self.component_type().is_integer() && between_stages {
+            shader.push_str("flat ");
+        }
         shader.push_str(pos.glsl_string());
         let gl_type = match self.value {
             Scalar => "float ".to_owned(),
             Vector(n) => format!("vec{} ", n as i32),
             Matrix(m, n) => format!("mat{}x{} ", m as i32, n as i32),
+            ScalarOf(component) => component.glsl_scalar().to_owned(),
+            VectorOf(component, n) => format!("{}vec{} ", component.glsl_prefix(), n as i32),
         };
         shader.push_str(&gl_type);
         shader.push_str(self.name());
@@ -0,0 +1,103 @@
+//! Various options to specify how pixels are copied between framebuffers
+//!
+//! Blitting copies a rectangle of pixels from one [`Surface`] (or the window) to another,
+//! scaling it if the rectangles are different sizes. See [`Context::blit`] and
+//! [`Surface::blit_to`].
+//!
+//! [`Surface`]: crate::Surface
+//! [`Context::blit`]: crate::Context::blit
+//! [`Surface::blit_to`]: crate::Surface::blit_to
+
+/// A rectangle of pixels in a framebuffer, measured from the bottom-left corner
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BlitRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BlitRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> BlitRegion {
+        BlitRegion {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The corners of the region, as GL expects them
+    pub(crate) fn corners(self) -> (i32, i32, i32, i32) {
+        (
+            self.x as i32,
+            self.y as i32,
+            (self.x + self.width) as i32,
+            (self.y + self.height) as i32,
+        )
+    }
+}
+
+/// Which buffers of the framebuffer are copied
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BlitMask {
+    /// Copy the color values
+    ///
+    /// Default is `true`.
+    pub color: bool,
+    /// Copy the depth values, which requires both framebuffers to have the same depth format
+    ///
+    /// Default is `false`.
+    pub depth: bool,
+    /// Copy the stencil values, which requires both framebuffers to have the same stencil format
+    ///
+    /// Default is `false`.
+    pub stencil: bool,
+}
+
+impl Default for BlitMask {
+    fn default() -> Self {
+        Self {
+            color: true,
+            depth: false,
+            stencil: false,
+        }
+    }
+}
+
+impl BlitMask {
+    pub(crate) fn to_gl(self) -> u32 {
+        let mut mask = 0;
+        if self.color {
+            mask |= glow::COLOR_BUFFER_BIT;
+        }
+        if self.depth {
+            mask |= glow::DEPTH_BUFFER_BIT;
+        }
+        if self.stencil {
+            mask |= glow::STENCIL_BUFFER_BIT;
+        }
+        mask
+    }
+}
+
+/// How pixels are sampled when the source and target regions are different sizes
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BlitFilter {
+    /// Pick the nearest source pixel
+    ///
+    /// This is the only filter available when copying depth or stencil values.
+    #[default]
+    Nearest,
+    /// Smooth out the source pixels as they stretch or squash
+    Linear,
+}
+
+impl BlitFilter {
+    pub(crate) fn to_gl(self) -> u32 {
+        match self {
+            BlitFilter::Nearest => glow::NEAREST,
+            BlitFilter::Linear => glow::LINEAR,
+        }
+    }
+}
@@ -1,15 +1,99 @@
 use crate::*;
+use core::cell::Cell;
 
 /// A buffer to store the vertices on the GPU
+///
+/// This holds interleaved f32 values. To store vertices with other component types, use a
+/// `Buffer` of a [`Vertex`] struct instead.
 pub type VertexBuffer = Buffer<f32>;
 
+/// A type that can be stored in a vertex buffer
+///
+/// `f32` is implemented out of the box, for buffers of interleaved floats like
+/// [`VertexBuffer`]. To use other component types (see [`ComponentType`]), implement this for a
+/// struct that holds one vertex, and create its buffer with [`Buffer::new`].
+///
+/// # Safety
+///
+/// The struct must be `#[repr(C)]`, and its fields must match the attributes of any
+/// [`ShaderProgram`] it is drawn with, in the same order and with the same size and component
+/// type. `golem` computes the attribute offsets with the same rules as `#[repr(C)]`, so any
+/// padding the compiler inserts between fields is accounted for.
+///
+/// ```rust
+/// # use golem::*;
+/// # use golem::Dimension::*;
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct ColoredVertex {
+///     position: [f32; 2],
+///     color: [u8; 4],
+/// }
+///
+/// unsafe impl bytemuck::Zeroable for ColoredVertex {}
+/// unsafe impl bytemuck::Pod for ColoredVertex {}
+/// unsafe impl Vertex for ColoredVertex {}
+///
+/// # fn func(ctx: &Context) -> Result<(), GolemError> {
+/// let vertex_input = [
+///     Attribute::new("vert_position", AttributeType::Vector(D2)),
+///     Attribute::new(
+///         "vert_color",
+///         AttributeType::VectorOf(ComponentType::U8 { normalized: true }, D4),
+///     ),
+/// ];
+/// let mut vb = Buffer::<ColoredVertex>::new(ctx)?;
+/// vb.set_data(&[ColoredVertex {
+///     position: [0.0, 0.5],
+///     color: [255, 0, 0, 255],
+/// }]);
+/// # Ok(()) }
+/// ```
+///
+/// [`ComponentType`]: crate::ComponentType
+/// [`ShaderProgram`]: crate::ShaderProgram
+pub unsafe trait Vertex: bytemuck::Pod {}
+
+unsafe impl Vertex for f32 {}
+
 /// A buffer to store the indices that make up the geometry elements
 ///
-/// See the [`GeometryMode`] for how the elements will be interpreted.
+/// See the [`GeometryMode`] for how the elements will be interpreted. For meshes with fewer
+/// vertices, a `Buffer<u16>` or `Buffer<u8>` can be used in the place of an `ElementBuffer` to
+/// save memory, see [`ElementIndex`].
 ///
 /// [`GeometryMode`]: crate::GeometryMode
 pub type ElementBuffer = Buffer<u32>;
 
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// A type that can be stored in an element buffer: `u8`, `u16`, or `u32`
+///
+/// Smaller index types use less memory and bandwidth, but can only refer to as many vertices as
+/// they can represent (256 for `u8` and 65,536 for `u16`).
+pub trait ElementIndex: bytemuck::Pod + sealed::Sealed {
+    #[doc(hidden)]
+    const GL_TYPE: u32;
+}
+
+impl ElementIndex for u8 {
+    const GL_TYPE: u32 = glow::UNSIGNED_BYTE;
+}
+
+impl ElementIndex for u16 {
+    const GL_TYPE: u32 = glow::UNSIGNED_SHORT;
+}
+
+impl ElementIndex for u32 {
+    const GL_TYPE: u32 = glow::UNSIGNED_INT;
+}
+
 /// A collection of values stored contiguously on the GPU, as either a [`VertexBuffer`] or an
 /// [`ElementBuffer`]
 ///
@@ -19,31 +103,44 @@ pub type ElementBuffer = Buffer<u32>;
 /// [`draw calls`]: crate::ShaderProgram::draw
 pub struct Buffer<T> {
     ctx: Context,
-    id: GlBuffer,
+    pub(crate) id: GlBuffer,
     length: usize,
+    /// The number of bytes at the start of the buffer that hold uploaded data
+    data_length: Cell<usize>,
     target: u32,
     _p: core::marker::PhantomData<T>,
 }
 
-impl Buffer<f32> {
+impl<V: Vertex> Buffer<V> {
     /// Create a [`VertexBuffer`] to store the vertex values
     pub fn new(ctx: &Context) -> Result<Self, GolemError> {
-        let ctx = Context(ctx.0.clone());
-        let id = unsafe { ctx.0.gl.create_buffer() }?;
-
-        Ok(Buffer {
-            ctx,
-            id,
-            length: 0,
-            target: glow::ARRAY_BUFFER,
-            _p: core::marker::PhantomData,
-        })
+        Self::with_target(ctx, glow::ARRAY_BUFFER)
     }
 }
 
 impl Buffer<u32> {
     /// Create a [`ElementBuffer`] to store the index values
     pub fn new(ctx: &Context) -> Result<Self, GolemError> {
+        Self::with_target(ctx, glow::ELEMENT_ARRAY_BUFFER)
+    }
+}
+
+impl Buffer<u16> {
+    /// Create an element buffer to store 16-bit index values
+    pub fn new(ctx: &Context) -> Result<Self, GolemError> {
+        Self::with_target(ctx, glow::ELEMENT_ARRAY_BUFFER)
+    }
+}
+
+impl Buffer<u8> {
+    /// Create an element buffer to store 8-bit index values
+    pub fn new(ctx: &Context) -> Result<Self, GolemError> {
+        Self::with_target(ctx, glow::ELEMENT_ARRAY_BUFFER)
+    }
+}
+
+impl<T> Buffer<T> {
+    pub(crate) fn with_target(ctx: &Context, target: u32) -> Result<Self, GolemError> {
         let ctx = Context(ctx.0.clone());
         let id = unsafe { ctx.0.gl.create_buffer() }?;
 
@@ -51,7 +148,8 @@ impl Buffer<u32> {
             ctx,
             id,
             length: 0,
-            target: glow::ELEMENT_ARRAY_BUFFER,
+            data_length: Cell::new(0),
+            target,
             _p: core::marker::PhantomData,
         })
     }
@@ -69,6 +167,11 @@ impl<T: bytemuck::Pod> Buffer<T> {
         self.length
     }
 
+    /// The size in bytes of the data uploaded to the buffer, which may be less than its capacity
+    pub(crate) fn data_size(&self) -> usize {
+        self.data_length.get()
+    }
+
     /// Set the data this buffer holds, resizing it if necessary
     ///
     /// The conditions under which the buffer is reallocated are an implementation detail, and it's
@@ -91,6 +194,7 @@ impl<T: bytemuck::Pod> Buffer<T> {
         unsafe {
             gl.buffer_sub_data_u8_slice(self.target, 0, u8_buffer);
         }
+        self.data_length.set(data_length);
     }
 
     /// Set some range of the buffer, within the existing capacity
@@ -111,6 +215,9 @@ impl<T: bytemuck::Pod> Buffer<T> {
                 .gl
                 .buffer_sub_data_u8_slice(self.target, start as i32, u8_buffer);
         }
+        if start + data_length > self.data_length.get() {
+            self.data_length.set(start + data_length);
+        }
     }
 }
 
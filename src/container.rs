@@ -0,0 +1,672 @@
+//! Parsing KTX2 and DDS texture files, enabled by the `containers` feature
+//!
+//! Both formats are read from bytes already in memory, so loading the file is up to the caller.
+//! A parsed [`TextureFile`] describes the format, dimensions, faces, layers, and mipmap levels of
+//! the image, and borrows the image data from the file's bytes until it is uploaded with
+//! [`TextureFile::create_texture`].
+//!
+//! ```rust
+//! # use golem::*;
+//! # use golem::container::TextureFile;
+//! # fn func(ctx: &Context, bytes: &[u8]) -> Result<(), GolemError> {
+//! let file = TextureFile::parse(bytes)?;
+//! let texture = file.create_texture(ctx)?;
+//! # Ok(()) }
+//! ```
+//!
+//! Supercompressed KTX2 files (such as Basis Universal), DDS files with bit-mask pixel formats
+//! other than 32-bit RGBA, and cube map arrays aren't supported, and return
+//! [`GolemError::InvalidTextureFile`].
+
+use crate::{ColorFormat, CompressedFormat, Context, CubeFace, GolemError, Texture, TextureKind};
+use std::borrow::ToOwned;
+use std::format;
+use std::vec::Vec;
+
+/// The format of the image data in a [`TextureFile`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// Uncompressed pixels, laid out as in [`Texture::set_image`]
+    Color(ColorFormat),
+    /// Block-compressed pixels, laid out as in [`Texture::set_compressed_image`]
+    Compressed(CompressedFormat),
+}
+
+impl TextureFormat {
+    /// The number of bytes a single image of the given size takes up
+    ///
+    /// Returns `None` if the size doesn't fit in a `usize`.
+    pub fn data_size(&self, width: u32, height: u32) -> Option<usize> {
+        match self {
+            TextureFormat::Color(color) => color.data_size(width, height, 1),
+            TextureFormat::Compressed(format) => format.data_size(width, height),
+        }
+    }
+}
+
+/// A texture parsed from a KTX2 or DDS file
+pub struct TextureFile<'a> {
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    depth: u32,
+    faces: u32,
+    layers: u32,
+    levels: u32,
+    /// Each image, indexed by layer, then face, then level
+    images: Vec<&'a [u8]>,
+}
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+impl<'a> TextureFile<'a> {
+    /// Parse either a KTX2 or a DDS file, depending on the identifier at the start of the data
+    pub fn parse(data: &'a [u8]) -> Result<TextureFile<'a>, GolemError> {
+        if data.starts_with(&KTX2_IDENTIFIER) {
+            TextureFile::parse_ktx2(data)
+        } else if data.starts_with(DDS_MAGIC) {
+            TextureFile::parse_dds(data)
+        } else {
+            Err(invalid("the data is neither a KTX2 nor a DDS file"))
+        }
+    }
+
+    /// Parse a KTX2 file
+    pub fn parse_ktx2(data: &'a [u8]) -> Result<TextureFile<'a>, GolemError> {
+        if !data.starts_with(&KTX2_IDENTIFIER) {
+            return Err(invalid("the KTX2 identifier is missing"));
+        }
+        let vk_format = read_u32(data, 12)?;
+        let width = read_u32(data, 20)?;
+        let height = read_u32(data, 24)?.max(1);
+        let depth = read_u32(data, 28)?.max(1);
+        let layers = read_u32(data, 32)?.max(1);
+        let faces = read_u32(data, 36)?;
+        // A level count of 0 asks for mipmaps to be generated from the one level given
+        let levels = read_u32(data, 40)?.max(1);
+        let supercompression = read_u32(data, 44)?;
+        if supercompression != 0 {
+            return Err(invalid("supercompressed KTX2 files aren't supported"));
+        }
+        let format = ktx2_format(vk_format)
+            .ok_or_else(|| invalid(&format!("the KTX2 format {} isn't supported", vk_format)))?;
+        let file = TextureFile::new(format, width, height, depth, faces, layers, levels)?;
+
+        // The level index follows the 80-byte header, and gives the range of each level, which
+        // holds each layer, then each face, then each depth slice
+        let mut level_images = Vec::new();
+        for level in 0..levels {
+            let entry = 80 + level as usize * 24;
+            let offset = read_u64(data, entry)?;
+            let length = read_u64(data, entry + 8)?;
+            let level_data = slice(data, offset, length)?;
+            let image_size = file.image_size(level)?;
+            let images = (0..layers * faces)
+                .map(|image| {
+                    let image_offset = (image as usize)
+                        .checked_mul(image_size)
+                        .ok_or_else(|| invalid("the level is too large"))?;
+                    slice(level_data, image_offset, image_size)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            level_images.push(images);
+        }
+
+        // Re-order the images from level-major to the layer-major order of DDS files
+        let mut images = Vec::new();
+        for image in 0..(layers * faces) as usize {
+            images.extend(level_images.iter().map(|level| level[image]));
+        }
+
+        Ok(TextureFile { images, ..file })
+    }
+
+    /// Parse a DDS file, with or without the DX10 header extension
+    pub fn parse_dds(data: &'a [u8]) -> Result<TextureFile<'a>, GolemError> {
+        if !data.starts_with(DDS_MAGIC) {
+            return Err(invalid("the DDS magic number is missing"));
+        }
+        const MIPMAP_COUNT: u32 = 0x2_0000;
+        const PIXEL_FORMAT_FOURCC: u32 = 0x4;
+        const PIXEL_FORMAT_RGB: u32 = 0x40;
+        const CUBE_MAP: u32 = 0x200;
+        const ALL_CUBE_FACES: u32 = 0xFC00;
+        const VOLUME: u32 = 0x20_0000;
+        const MISC_TEXTURE_CUBE: u32 = 0x4;
+
+        let flags = read_u32(data, 8)?;
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let caps2 = read_u32(data, 112)?;
+        let depth = if caps2 & VOLUME != 0 {
+            read_u32(data, 24)?.max(1)
+        } else {
+            1
+        };
+        let levels = if flags & MIPMAP_COUNT != 0 {
+            read_u32(data, 28)?.max(1)
+        } else {
+            1
+        };
+        let pixel_flags = read_u32(data, 80)?;
+        let four_cc = slice(data, 84, 4)?;
+        let mut faces = if caps2 & CUBE_MAP != 0 {
+            if caps2 & ALL_CUBE_FACES != ALL_CUBE_FACES {
+                return Err(invalid("DDS cube maps must have all six faces"));
+            }
+            6
+        } else {
+            1
+        };
+        let mut layers = 1;
+        let mut data_start = 128;
+        let format = if pixel_flags & PIXEL_FORMAT_FOURCC != 0 {
+            match four_cc {
+                b"DX10" => {
+                    let dxgi_format = read_u32(data, 128)?;
+                    if read_u32(data, 136)? & MISC_TEXTURE_CUBE != 0 {
+                        faces = 6;
+                    }
+                    layers = read_u32(data, 140)?.max(1);
+                    data_start = 148;
+                    dxgi_format_to_format(dxgi_format).ok_or_else(|| {
+                        invalid(&format!("the DXGI format {} isn't supported", dxgi_format))
+                    })?
+                }
+                b"DXT1" => TextureFormat::Compressed(CompressedFormat::BC1A),
+                b"DXT2" | b"DXT3" => TextureFormat::Compressed(CompressedFormat::BC2),
+                b"DXT4" | b"DXT5" => TextureFormat::Compressed(CompressedFormat::BC3),
+                b"ATI1" | b"BC4U" => TextureFormat::Compressed(CompressedFormat::BC4),
+                b"ATI2" | b"BC5U" => TextureFormat::Compressed(CompressedFormat::BC5),
+                _ => return Err(invalid("the DDS FourCC format isn't supported")),
+            }
+        } else if pixel_flags & PIXEL_FORMAT_RGB != 0
+            && read_u32(data, 88)? == 32
+            && read_u32(data, 92)? == 0x0000_00FF
+            && read_u32(data, 96)? == 0x0000_FF00
+            && read_u32(data, 100)? == 0x00FF_0000
+        {
+            TextureFormat::Color(ColorFormat::RGBA)
+        } else {
+            return Err(invalid("the DDS pixel format isn't supported"));
+        };
+        let file = TextureFile::new(format, width, height, depth, faces, layers, levels)?;
+
+        // Each layer holds each face, which holds each level
+        let mut images = Vec::new();
+        let mut offset = data_start;
+        for _ in 0..layers * faces {
+            for level in 0..levels {
+                let size = file.image_size(level)?;
+                images.push(slice(data, offset, size)?);
+                offset += size;
+            }
+        }
+
+        Ok(TextureFile { images, ..file })
+    }
+
+    fn new(
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        depth: u32,
+        faces: u32,
+        layers: u32,
+        levels: u32,
+    ) -> Result<TextureFile<'a>, GolemError> {
+        if width == 0 || height == 0 {
+            return Err(invalid("the image is empty"));
+        }
+        if faces != 1 && faces != 6 {
+            return Err(invalid("the image must have 1 or 6 faces"));
+        }
+        if layers.checked_mul(faces).is_none() {
+            return Err(invalid("there are too many layers"));
+        }
+        if faces == 6 && (layers > 1 || width != height) {
+            return Err(invalid("cube maps must be square, and can't be arrays"));
+        }
+        if depth > 1 && (layers > 1 || faces > 1) {
+            return Err(invalid("3D textures can't be arrays or cube maps"));
+        }
+        if levels > 32 - width.max(height).max(depth).leading_zeros() {
+            return Err(invalid("there are more levels than the size allows"));
+        }
+
+        Ok(TextureFile {
+            format,
+            width,
+            height,
+            depth,
+            faces,
+            layers,
+            levels,
+            images: Vec::new(),
+        })
+    }
+
+    /// The format of the image data
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// The width of the full-size image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the full-size image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The depth of a 3D texture, or 1 for any other texture
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// The number of faces: 6 for a cube map, or 1 for any other texture
+    pub fn faces(&self) -> u32 {
+        self.faces
+    }
+
+    /// The number of layers of a texture array, or 1 for any other texture
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// The number of mipmap levels stored in the file, including the full-size image
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// The size of a mipmap level, as `(width, height, depth)`
+    pub fn level_size(&self, level: u32) -> (u32, u32, u32) {
+        (
+            (self.width >> level).max(1),
+            (self.height >> level).max(1),
+            (self.depth >> level).max(1),
+        )
+    }
+
+    /// The number of bytes one image of a mipmap level takes up, including every depth slice
+    fn image_size(&self, level: u32) -> Result<usize, GolemError> {
+        let (width, height, depth) = self.level_size(level);
+        self.format
+            .data_size(width, height)
+            .and_then(|size| size.checked_mul(depth as usize))
+            .ok_or_else(|| invalid("the image is too large"))
+    }
+
+    /// The data of one image: a mipmap level of one face of one layer
+    ///
+    /// The data of a 3D texture holds every depth slice of the level, one after another. If the
+    /// level, layer, or face is out of range, this will panic.
+    pub fn image(&self, level: u32, layer: u32, face: u32) -> &'a [u8] {
+        assert!(level < self.levels, "The level was out of range");
+        assert!(layer < self.layers, "The layer was out of range");
+        assert!(face < self.faces, "The face was out of range");
+        let index = (layer * self.faces + face) * self.levels + level;
+
+        self.images[index as usize]
+    }
+
+    /// Create a texture with the file's images
+    ///
+    /// Files with 6 faces create cube maps, files with more than one layer create texture
+    /// arrays, and files with a depth create 3D textures. All of the levels in the file are
+    /// uploaded; if it only has the full-size image, mipmaps are generated like
+    /// [`Texture::set_image`]. Compressed 3D textures aren't supported.
+    ///
+    /// If the file's size or layers are beyond the GPU's limits (like [`glow::MAX_TEXTURE_SIZE`]
+    /// or [`glow::MAX_ARRAY_TEXTURE_LAYERS`]), or it's a compressed 3D texture, this returns
+    /// [`GolemError::InvalidTextureFile`]. If the context doesn't support a compressed format,
+    /// this returns [`GolemError::ExtensionUnavailable`], like [`Texture::set_compressed_image`].
+    pub fn create_texture(&self, ctx: &Context) -> Result<Texture, GolemError> {
+        let kind = if self.faces == 6 {
+            TextureKind::CubeMap
+        } else if self.depth > 1 {
+            TextureKind::Texture3D
+        } else if self.layers > 1 {
+            TextureKind::Texture2DArray
+        } else {
+            TextureKind::Texture2D
+        };
+        // The texture functions assert their limits, so a file beyond them is reported instead
+        let max_size = ctx.max_texture_size(kind);
+        if self.width > max_size || self.height > max_size {
+            return Err(invalid(&format!(
+                "the image is bigger than the maximum size of {}",
+                max_size
+            )));
+        }
+        let max_layers = ctx.max_texture_layers(kind);
+        if self.depth.max(self.layers) > max_layers {
+            return Err(invalid(&format!(
+                "the image has more layers than the maximum of {}",
+                max_layers
+            )));
+        }
+        let mut texture = match kind {
+            TextureKind::CubeMap => Texture::new_cube_map(ctx)?,
+            TextureKind::Texture3D => Texture::new_3d(ctx)?,
+            TextureKind::Texture2DArray => Texture::new_array(ctx)?,
+            TextureKind::Texture2D => Texture::new(ctx)?,
+        };
+        match self.format {
+            TextureFormat::Color(color) => self.upload_color(&mut texture, color),
+            TextureFormat::Compressed(format) => self.upload_compressed(&mut texture, format)?,
+        }
+
+        Ok(texture)
+    }
+
+    fn upload_color(&self, texture: &mut Texture, color: ColorFormat) {
+        // The full-size images may generate mipmaps, which the levels from the file replace
+        let (width, height) = (self.width, self.height);
+        if self.faces == 6 {
+            // Mipmaps are generated once the last face is set, so every face comes first
+            for (index, face) in CubeFace::ALL.iter().enumerate() {
+                let data = self.image(0, 0, index as u32);
+                texture.set_face_image(*face, Some(data), width, color);
+            }
+            for level in 1..self.levels {
+                for (index, face) in CubeFace::ALL.iter().enumerate() {
+                    let data = self.image(level, 0, index as u32);
+                    texture.upload_level(Some(*face), level, data);
+                }
+            }
+        } else if self.depth > 1 || self.layers > 1 {
+            let layers = self.depth.max(self.layers);
+            let data = self.layered_level(0);
+            texture.set_image_3d(Some(&data), width, height, layers, color);
+            for level in 1..self.levels {
+                texture.upload_level(None, level, &self.layered_level(level));
+            }
+        } else {
+            texture.set_image(Some(self.image(0, 0, 0)), width, height, color);
+            for level in 1..self.levels {
+                texture.upload_level(None, level, self.image(level, 0, 0));
+            }
+        }
+    }
+
+    fn upload_compressed(
+        &self,
+        texture: &mut Texture,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        let (width, height) = (self.width, self.height);
+        if self.faces == 6 {
+            for (index, face) in CubeFace::ALL.iter().enumerate() {
+                let levels = self.level_images(0, index as u32);
+                texture.set_compressed_face_image(*face, &levels, width, format)?;
+            }
+        } else if self.depth > 1 {
+            return Err(invalid("compressed 3D textures aren't supported"));
+        } else if self.layers > 1 {
+            let levels = (0..self.levels)
+                .map(|level| self.layered_level(level))
+                .collect::<Vec<_>>();
+            let levels = levels.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            texture.set_compressed_image_3d(&levels, width, height, self.layers, format)?;
+        } else {
+            texture.set_compressed_image(&self.level_images(0, 0), width, height, format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Every level of one face of one layer
+    fn level_images(&self, layer: u32, face: u32) -> Vec<&'a [u8]> {
+        (0..self.levels)
+            .map(|level| self.image(level, layer, face))
+            .collect()
+    }
+
+    /// One level of every layer (or every depth slice of a 3D texture), joined together
+    fn layered_level(&self, level: u32) -> Vec<u8> {
+        (0..self.layers)
+            .flat_map(|layer| self.image(level, layer, 0).iter().copied())
+            .collect()
+    }
+}
+
+fn invalid(reason: &str) -> GolemError {
+    GolemError::InvalidTextureFile(reason.to_owned())
+}
+
+fn slice(data: &[u8], offset: usize, length: usize) -> Result<&[u8], GolemError> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| invalid("the file is shorter than its header describes"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, GolemError> {
+    let bytes = slice(data, offset, 4)?;
+
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<usize, GolemError> {
+    let low = read_u32(data, offset)? as u64;
+    let high = read_u32(data, offset + 4)? as u64;
+    let value = low | (high << 32);
+    if value > usize::MAX as u64 {
+        return Err(invalid("an offset is too large"));
+    }
+
+    Ok(value as usize)
+}
+
+/// Map a `VkFormat` from a KTX2 header
+fn ktx2_format(vk_format: u32) -> Option<TextureFormat> {
+    use TextureFormat::*;
+    Some(match vk_format {
+        9 => Color(ColorFormat::R8),
+        16 => Color(ColorFormat::RG8),
+        23 => Color(ColorFormat::RGB),
+        29 => Color(ColorFormat::SRGB),
+        37 => Color(ColorFormat::RGBA),
+        43 => Color(ColorFormat::SRGBA),
+        76 => Color(ColorFormat::R16F),
+        97 => Color(ColorFormat::RGBA16F),
+        98 => Color(ColorFormat::R32UI),
+        99 => Color(ColorFormat::R32I),
+        100 => Color(ColorFormat::R32F),
+        109 => Color(ColorFormat::RGBA32F),
+        131 => Compressed(CompressedFormat::BC1),
+        133 => Compressed(CompressedFormat::BC1A),
+        135 => Compressed(CompressedFormat::BC2),
+        137 => Compressed(CompressedFormat::BC3),
+        139 => Compressed(CompressedFormat::BC4),
+        141 => Compressed(CompressedFormat::BC5),
+        145 => Compressed(CompressedFormat::BC7),
+        146 => Compressed(CompressedFormat::BC7SRGB),
+        147 => Compressed(CompressedFormat::ETC2RGB),
+        151 => Compressed(CompressedFormat::ETC2RGBA),
+        157 => Compressed(CompressedFormat::ASTC4x4),
+        165 => Compressed(CompressedFormat::ASTC6x6),
+        171 => Compressed(CompressedFormat::ASTC8x8),
+        _ => return None,
+    })
+}
+
+/// Map a `DXGI_FORMAT` from a DDS DX10 header
+fn dxgi_format_to_format(dxgi_format: u32) -> Option<TextureFormat> {
+    use TextureFormat::*;
+    Some(match dxgi_format {
+        2 => Color(ColorFormat::RGBA32F),
+        10 => Color(ColorFormat::RGBA16F),
+        28 => Color(ColorFormat::RGBA),
+        29 => Color(ColorFormat::SRGBA),
+        41 => Color(ColorFormat::R32F),
+        42 => Color(ColorFormat::R32UI),
+        43 => Color(ColorFormat::R32I),
+        49 => Color(ColorFormat::RG8),
+        54 => Color(ColorFormat::R16F),
+        61 => Color(ColorFormat::R8),
+        71 => Compressed(CompressedFormat::BC1A),
+        74 => Compressed(CompressedFormat::BC2),
+        77 => Compressed(CompressedFormat::BC3),
+        80 => Compressed(CompressedFormat::BC4),
+        83 => Compressed(CompressedFormat::BC5),
+        98 => Compressed(CompressedFormat::BC7),
+        99 => Compressed(CompressedFormat::BC7SRGB),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+    use std::vec;
+
+    const VK_R8G8B8A8_UNORM: u32 = 37;
+    const VK_R32G32B32A32_SFLOAT: u32 = 109;
+
+    /// A KTX2 file with one layer and face, and the levels stored one after another
+    fn ktx2(vk_format: u32, width: u32, height: u32, layers: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut data = KTX2_IDENTIFIER.to_vec();
+        for field in [
+            vk_format,
+            1,
+            width,
+            height,
+            0,
+            layers,
+            1,
+            levels.len() as u32,
+            0,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.resize(80, 0);
+        let mut offset = 80 + levels.len() * 24;
+        for level in levels {
+            for field in [offset, level.len(), level.len()] {
+                data.extend_from_slice(&(field as u64).to_le_bytes());
+            }
+            offset += level.len();
+        }
+        for level in levels {
+            data.extend_from_slice(level);
+        }
+
+        data
+    }
+
+    /// A DDS file with a FourCC format, and the levels stored one after another
+    fn dds(four_cc: &[u8; 4], width: u32, height: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut data = vec![0; 128];
+        data[..4].copy_from_slice(DDS_MAGIC);
+        let mut write = |offset: usize, value: u32| {
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        write(4, 124);
+        write(8, 0x2_0000);
+        write(12, height);
+        write(16, width);
+        write(28, levels.len() as u32);
+        write(76, 32);
+        write(80, 0x4);
+        data[84..88].copy_from_slice(four_cc);
+        for level in levels {
+            data.extend_from_slice(level);
+        }
+
+        data
+    }
+
+    fn parse_error(data: &[u8]) -> String {
+        match TextureFile::parse(data) {
+            Err(GolemError::InvalidTextureFile(reason)) => reason,
+            Err(other) => panic!("The wrong error was returned: {}", other),
+            Ok(_) => panic!("The file was parsed"),
+        }
+    }
+
+    #[test]
+    fn parses_ktx2_levels() {
+        let level0 = [1; 4 * 2 * 4];
+        let level1 = [2; 2 * 4];
+        let data = ktx2(VK_R8G8B8A8_UNORM, 4, 2, 0, &[&level0, &level1]);
+        let file = TextureFile::parse(&data).expect("The file is valid");
+        assert_eq!(file.format(), TextureFormat::Color(ColorFormat::RGBA));
+        assert_eq!((file.width(), file.height(), file.layers()), (4, 2, 1));
+        assert_eq!(file.levels(), 2);
+        assert_eq!(file.image(0, 0, 0), &level0[..]);
+        assert_eq!(file.image(1, 0, 0), &level1[..]);
+    }
+
+    #[test]
+    fn parses_ktx2_layers() {
+        // Each level holds every layer, one after another
+        let level: Vec<u8> = (0..2 * 2 * 4 * 3).collect();
+        let data = ktx2(VK_R8G8B8A8_UNORM, 2, 2, 3, &[&level]);
+        let file = TextureFile::parse(&data).expect("The file is valid");
+        assert_eq!(file.layers(), 3);
+        assert_eq!(file.image(0, 2, 0), &level[32..]);
+    }
+
+    #[test]
+    fn parses_dds_compressed_levels() {
+        let level0 = [1; 4 * 8];
+        let level1 = [2; 8];
+        let data = dds(b"DXT1", 8, 8, &[&level0, &level1]);
+        let file = TextureFile::parse(&data).expect("The file is valid");
+        assert_eq!(
+            file.format(),
+            TextureFormat::Compressed(CompressedFormat::BC1A)
+        );
+        assert_eq!(file.levels(), 2);
+        assert_eq!(file.image(0, 0, 0), &level0[..]);
+        assert_eq!(file.image(1, 0, 0), &level1[..]);
+    }
+
+    #[test]
+    fn rejects_unknown_data() {
+        parse_error(b"not a texture file");
+        parse_error(&[]);
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let level = [0; 4 * 4 * 4];
+        let ktx2 = ktx2(VK_R8G8B8A8_UNORM, 4, 4, 0, &[&level]);
+        for length in [20, 80, ktx2.len() - 1] {
+            parse_error(&ktx2[..length]);
+        }
+        let dds = dds(b"DXT5", 4, 4, &[&[0; 16]]);
+        for length in [20, 100, dds.len() - 1] {
+            parse_error(&dds[..length]);
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_sizes() {
+        let data = ktx2(VK_R32G32B32A32_SFLOAT, u32::MAX, u32::MAX, 0, &[&[0; 16]]);
+        assert_eq!(parse_error(&data), "the image is too large");
+        let data = dds(b"DXT5", u32::MAX, u32::MAX, &[&[0; 16]]);
+        assert_eq!(parse_error(&data), "the image is too large");
+        // The layers are read until the data runs out, rather than all being allocated
+        let data = ktx2(VK_R8G8B8A8_UNORM, 1, 1, u32::MAX, &[&[0; 4]]);
+        parse_error(&data);
+    }
+
+    #[test]
+    fn rejects_too_many_levels() {
+        let levels: [&[u8]; 4] = [&[0; 64], &[0; 16], &[0; 4], &[0; 4]];
+        let data = ktx2(VK_R8G8B8A8_UNORM, 4, 4, 0, &levels);
+        assert_eq!(
+            parse_error(&data),
+            "there are more levels than the size allows"
+        );
+    }
+}
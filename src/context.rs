@@ -1,11 +1,17 @@
 use crate::blend::{BlendEquation, BlendFunction, BlendMode};
+use crate::blit::{BlitFilter, BlitMask, BlitRegion};
 use crate::depth::DepthTestMode;
-use crate::{GlFramebuffer, GlProgram, GlVertexArray, GolemError};
+use crate::stencil::StencilTestMode;
+use crate::{
+    CompressedFormat, GlFramebuffer, GlProgram, GlVertexArray, GolemError, Surface, TextureKind,
+};
 use core::cell::RefCell;
 #[cfg(not(target_arch = "wasm32"))]
 use core::ffi::{c_void, CStr};
 use glow::HasContext;
+use std::format;
 use std::rc::Rc;
+use std::vec::Vec;
 #[cfg(target_arch = "wasm32")]
 use web_sys::WebGl2RenderingContext;
 
@@ -18,6 +24,11 @@ pub(crate) struct ContextContents {
     pub(crate) current_surface: RefCell<Option<GlFramebuffer>>,
     vao: GlVertexArray,
     max_vertex_attrib_index: RefCell<u32>,
+    /// The vertex attributes with a nonzero instance divisor
+    instanced_attributes: RefCell<Vec<u32>>,
+    uniform_block_bindings: RefCell<Vec<&'static str>>,
+    /// MAX_COMBINED_TEXTURE_IMAGE_UNITS, which can't change after the context is created
+    max_texture_units: u32,
 }
 
 impl Drop for ContextContents {
@@ -64,9 +75,18 @@ impl Context {
             // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBindVertexArray.xhtml
             // In this case, we know 'vao' must be a valid vao because we just constructed it
             gl.bind_vertex_array(Some(vao));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glPixelStorei.xhtml
+            // Pixel data is always tightly packed, but rows are aligned to 4 bytes by default, which
+            // would read and write past the end of rows like those of a 3x3 R8 image
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
 
             vao
         };
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGet.xhtml
+        // The limit is fixed for the lifetime of the context, so it's only queried once
+        let max_texture_units =
+            unsafe { gl.get_parameter_i32(glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS) as u32 };
 
         let contents = Context(Rc::new(ContextContents {
             gl,
@@ -74,6 +94,9 @@ impl Context {
             current_surface: RefCell::new(None),
             vao,
             max_vertex_attrib_index: RefCell::new(0),
+            instanced_attributes: RefCell::new(Vec::new()),
+            uniform_block_bindings: RefCell::new(Vec::new()),
+            max_texture_units,
         }));
         contents.set_clear_color(0.0, 0.0, 0.0, 1.0);
 
@@ -129,13 +152,30 @@ impl Context {
         }
     }
 
+    /// Set the value the stencil buffer will be cleared to by [`clear`]
+    ///
+    /// By default, this is 0
+    ///
+    /// [`clear`]: Context::clear
+    pub fn set_clear_stencil(&self, value: i32) {
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glClearStencil.xhtml
+        unsafe {
+            self.0.gl.clear_stencil(value);
+        }
+    }
+
     /// Clear the current render target to the render color (see [`set_clear_color`])
     ///
+    /// The depth buffer is also cleared, as well as the stencil buffer (see
+    /// [`set_clear_stencil`]). While a stencil mode is set, only the bits of the stencil buffer
+    /// enabled by its [`StencilTestMode::write_mask`] are cleared.
+    ///
     /// [`set_clear_color`]: Context::set_clear_color
+    /// [`set_clear_stencil`]: Context::set_clear_stencil
     pub fn clear(&self) {
         let gl = &self.0.gl;
         unsafe {
-            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT);
         }
     }
 
@@ -241,6 +281,270 @@ impl Context {
         }
     }
 
+    /// Set the stencil test mode, with `None` disabling stencil testing
+    ///
+    /// By default, this is `None`
+    ///
+    /// The render target must have a stencil buffer for this to have any effect. See the
+    /// documentation for [`StencilTestMode`](stencil/struct.StencilTestMode.html) for the
+    /// various stencil testing options
+    pub fn set_stencil_mode(&self, stencil_state: Option<StencilTestMode>) {
+        let gl = &self.0.gl;
+        match stencil_state {
+            Some(StencilTestMode {
+                function,
+                reference,
+                test_mask,
+                write_mask,
+                front,
+                back,
+            }) => unsafe {
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glEnable.xhtml
+                gl.enable(glow::STENCIL_TEST);
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glStencilFunc.xhtml
+                // The to_gl() function only produces valid values
+                gl.stencil_func(function.to_gl(), reference, test_mask);
+
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glStencilMask.xhtml
+                gl.stencil_mask(write_mask);
+
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glStencilOpSeparate.xhtml
+                // The to_gl() function only produces valid values
+                gl.stencil_op_separate(
+                    glow::FRONT,
+                    front.stencil_fail.to_gl(),
+                    front.depth_fail.to_gl(),
+                    front.pass.to_gl(),
+                );
+                gl.stencil_op_separate(
+                    glow::BACK,
+                    back.stencil_fail.to_gl(),
+                    back.depth_fail.to_gl(),
+                    back.pass.to_gl(),
+                );
+            },
+            None => unsafe {
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glEnable.xhtml
+                gl.disable(glow::STENCIL_TEST);
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glStencilMask.xhtml
+                // The write mask also applies to clears, so every bit is written again
+                gl.stencil_mask(!0);
+            },
+        }
+    }
+
+    /// Copy a rectangle of pixels from one framebuffer to another
+    ///
+    /// `None` for either the source or the target refers to the window. If the regions are
+    /// different sizes, the pixels are scaled using the `filter`. Copying depth or stencil values
+    /// requires [`BlitFilter::Nearest`] (which is checked for via an `assert!`.)
+    ///
+    /// The source and target must not be the same surface.
+    pub fn blit(
+        &self,
+        source: Option<&Surface>,
+        source_region: BlitRegion,
+        target: Option<&Surface>,
+        target_region: BlitRegion,
+        mask: BlitMask,
+        filter: BlitFilter,
+    ) {
+        if let (Some(source), Some(target)) = (source, target) {
+            assert!(
+                source.id != target.id,
+                "The source and target of a blit were the same surface"
+            );
+        }
+        self.blit_framebuffer(
+            source.map(|surface| surface.id),
+            source_region,
+            target.map(|surface| surface.id),
+            target_region,
+            mask,
+            filter,
+        );
+    }
+
+    pub(crate) fn blit_framebuffer(
+        &self,
+        source: Option<GlFramebuffer>,
+        source_region: BlitRegion,
+        target: Option<GlFramebuffer>,
+        target_region: BlitRegion,
+        mask: BlitMask,
+        filter: BlitFilter,
+    ) {
+        assert!(
+            filter == BlitFilter::Nearest || !(mask.depth || mask.stencil),
+            "Depth and stencil values can only be blitted with BlitFilter::Nearest"
+        );
+        let (src_x0, src_y0, src_x1, src_y1) = source_region.corners();
+        let (dst_x0, dst_y0, dst_x1, dst_y1) = target_region.corners();
+        let gl = &self.0.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, source);
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, target);
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glBlitFramebuffer.xhtml
+            // Errors:
+            // 1. The mask only contains the color, depth, and stencil bits
+            // 2. Filtering depth or stencil with LINEAR is ruled out above
+            // 3. Mismatched depth or stencil formats, integer formats, or multisample sizes are
+            //    reported by the GL
+            gl.blit_framebuffer(
+                src_x0,
+                src_y0,
+                src_x1,
+                src_y1,
+                dst_x0,
+                dst_y0,
+                dst_x1,
+                dst_y1,
+                mask.to_gl(),
+                filter.to_gl(),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.0.current_surface.borrow());
+        }
+    }
+
+    /// The highest level of anisotropic filtering supported, if it's supported at all
+    ///
+    /// Anisotropic filtering requires the `EXT_texture_filter_anisotropic` extension. See
+    /// [`Texture::set_anisotropy`](crate::Texture::set_anisotropy).
+    pub fn max_anisotropy(&self) -> Option<f32> {
+        if self.has_extension("EXT_texture_filter_anisotropic")
+            || self.has_extension("ARB_texture_filter_anisotropic")
+        {
+            Some(unsafe {
+                self.0
+                    .gl
+                    .get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT)
+            })
+        } else {
+            None
+        }
+    }
+
+    /// If textures can be uploaded in the given compressed format
+    pub fn supports_compressed_format(&self, format: CompressedFormat) -> bool {
+        let extensions = format.extensions();
+        extensions.is_empty() || extensions.iter().any(|name| self.has_extension(name))
+    }
+
+    /// All of the compressed formats that textures can be uploaded in
+    pub fn compressed_formats(&self) -> Vec<CompressedFormat> {
+        CompressedFormat::ALL
+            .iter()
+            .copied()
+            .filter(|format| self.supports_compressed_format(*format))
+            .collect()
+    }
+
+    /// Check for an extension by name, without the `GL_` prefix used on desktop
+    pub(crate) fn has_extension(&self, name: &str) -> bool {
+        let extensions = self.0.gl.supported_extensions();
+        extensions.contains(name) || extensions.contains(&format!("GL_{}", name))
+    }
+
+    /// Check for a feature that is core in the given desktop GL version, or provided by an extension
+    ///
+    /// Everything checked this way is core in OpenGL ES 3.0 and WebGL 2.
+    pub(crate) fn has_feature(&self, major: u32, minor: u32, extension: &str) -> bool {
+        let version = self.0.gl.version();
+        version.is_embedded
+            || (version.major, version.minor) >= (major, minor)
+            || self.has_extension(extension)
+    }
+
+    /// Set the instance divisor of a vertex attribute
+    ///
+    /// Divisors require OpenGL 3.3 or ARB_instanced_arrays, so the GL is only called to set a
+    /// nonzero divisor (which instanced draws check for) or to reset one that was set before.
+    pub(crate) fn set_attribute_divisor(&self, index: u32, divisor: u32) {
+        let mut instanced = self.0.instanced_attributes.borrow_mut();
+        let was_instanced = instanced.contains(&index);
+        if divisor == 0 {
+            if !was_instanced {
+                return;
+            }
+            instanced.retain(|attribute| *attribute != index);
+        } else if !was_instanced {
+            instanced.push(index);
+        }
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glVertexAttribDivisor.xhtml
+        // The index is below MAX_VERTEX_ATTRIBS, because the attribute was just enabled
+        unsafe {
+            self.0.gl.vertex_attrib_divisor(index, divisor);
+        }
+    }
+
+    /// The number of texture units the GPU supports across all shader stages
+    pub(crate) fn max_texture_units(&self) -> u32 {
+        self.0.max_texture_units
+    }
+
+    /// The largest width and height the GPU supports for a kind of texture
+    pub(crate) fn max_texture_size(&self, kind: TextureKind) -> u32 {
+        let parameter = match kind {
+            TextureKind::CubeMap => glow::MAX_CUBE_MAP_TEXTURE_SIZE,
+            TextureKind::Texture3D => glow::MAX_3D_TEXTURE_SIZE,
+            _ => glow::MAX_TEXTURE_SIZE,
+        };
+        unsafe { self.0.gl.get_parameter_i32(parameter) as u32 }
+    }
+
+    /// The most layers (or depth, for 3D textures) the GPU supports for a kind of texture
+    pub(crate) fn max_texture_layers(&self, kind: TextureKind) -> u32 {
+        let parameter = match kind {
+            TextureKind::Texture2DArray => glow::MAX_ARRAY_TEXTURE_LAYERS,
+            TextureKind::Texture3D => glow::MAX_3D_TEXTURE_SIZE,
+            _ => return 1,
+        };
+        unsafe { self.0.gl.get_parameter_i32(parameter) as u32 }
+    }
+
+    /// Check an anisotropy level for a texture or sampler, against the supported maximum
+    pub(crate) fn check_anisotropy(&self, level: f32) -> Result<(), GolemError> {
+        let max = self
+            .max_anisotropy()
+            .ok_or(GolemError::ExtensionUnavailable(
+                "EXT_texture_filter_anisotropic",
+            ))?;
+        assert!(
+            level >= 1.0 && level <= max,
+            "The anisotropy level must be between 1 and {}",
+            max
+        );
+
+        Ok(())
+    }
+
+    /// Find the binding point shared by every uniform block with the given name
+    ///
+    /// Binding points are allocated the first time a block name is seen, by either a
+    /// [`ShaderProgram`](crate::ShaderProgram) or a [`UniformBuffer`](crate::UniformBuffer).
+    pub(crate) fn uniform_block_binding(&self, name: &'static str) -> Result<u32, GolemError> {
+        let mut bindings = self.0.uniform_block_bindings.borrow_mut();
+        if let Some(binding) = bindings.iter().position(|block| *block == name) {
+            return Ok(binding as u32);
+        }
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glUniformBlockBinding.xhtml
+        // The binding must be less than MAX_UNIFORM_BUFFER_BINDINGS
+        let max_bindings = unsafe {
+            self.0
+                .gl
+                .get_parameter_i32(glow::MAX_UNIFORM_BUFFER_BINDINGS)
+        };
+        if bindings.len() >= max_bindings as usize {
+            return Err(GolemError::ContextError(format!(
+                "Too many uniform blocks; the maximum is {}",
+                max_bindings
+            )));
+        }
+        bindings.push(name);
+
+        Ok(bindings.len() as u32 - 1)
+    }
+
     /// Set the new max attribute, clear the old one
     pub(crate) fn max_attrib(&self, index: u32) -> u32 {
         let mut attrib_ptr = self.0.max_vertex_attrib_index.borrow_mut();
@@ -47,11 +47,12 @@ impl Default for DepthTestMode {
 ///
 /// "Depth value" is, roughly, how far away the pixel is from the camera; you'll almost always
 /// want `DepthTestFunction::Less` (which is the default).
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
 pub enum DepthTestFunction {
     /// Pixel is never drawn
     Never,
     /// Pixel is drawn if it's depth value is less than the stored one
+    #[default]
     Less,
     /// Pixel is drawn if it's depth value is equal to the stored one
     Equal,
@@ -67,12 +68,6 @@ pub enum DepthTestFunction {
     Always,
 }
 
-impl Default for DepthTestFunction {
-    fn default() -> Self {
-        DepthTestFunction::Less
-    }
-}
-
 impl DepthTestFunction {
     #[allow(clippy::wrong_self_convention)] // TODO maybe this should be addressed properly.
     pub(crate) fn to_gl(self) -> u32 {
@@ -89,3 +84,74 @@ impl DepthTestFunction {
         }
     }
 }
+
+/// How depth (and optionally stencil) values are stored in a depth buffer
+///
+/// Used to give a [`Surface`] a depth buffer, either with [`Surface::set_depth_buffer`] or with
+/// a depth texture created by [`Texture::set_depth_image`].
+///
+/// [`Surface`]: crate::Surface
+/// [`Surface::set_depth_buffer`]: crate::Surface::set_depth_buffer
+/// [`Texture::set_depth_image`]: crate::Texture::set_depth_image
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DepthFormat {
+    /// 16-bit depth values
+    Depth16,
+    /// 24-bit depth values
+    Depth24,
+    /// 32-bit floating-point depth values
+    Depth32F,
+    /// 24-bit depth values, with an 8-bit stencil buffer
+    Depth24Stencil8,
+    /// 32-bit floating-point depth values, with an 8-bit stencil buffer
+    Depth32FStencil8,
+}
+
+impl DepthFormat {
+    /// If this format also includes a stencil buffer
+    pub fn has_stencil(self) -> bool {
+        matches!(
+            self,
+            DepthFormat::Depth24Stencil8 | DepthFormat::Depth32FStencil8
+        )
+    }
+
+    pub(crate) fn gl_internal_format(self) -> u32 {
+        use DepthFormat::*;
+        match self {
+            Depth16 => glow::DEPTH_COMPONENT16,
+            Depth24 => glow::DEPTH_COMPONENT24,
+            Depth32F => glow::DEPTH_COMPONENT32F,
+            Depth24Stencil8 => glow::DEPTH24_STENCIL8,
+            Depth32FStencil8 => glow::DEPTH32F_STENCIL8,
+        }
+    }
+
+    pub(crate) fn gl_format(self) -> u32 {
+        if self.has_stencil() {
+            glow::DEPTH_STENCIL
+        } else {
+            glow::DEPTH_COMPONENT
+        }
+    }
+
+    pub(crate) fn gl_type(self) -> u32 {
+        use DepthFormat::*;
+        match self {
+            Depth16 => glow::UNSIGNED_SHORT,
+            Depth24 => glow::UNSIGNED_INT,
+            Depth32F => glow::FLOAT,
+            Depth24Stencil8 => glow::UNSIGNED_INT_24_8,
+            Depth32FStencil8 => glow::FLOAT_32_UNSIGNED_INT_24_8_REV,
+        }
+    }
+
+    /// The framebuffer attachment point for a buffer of this format
+    pub(crate) fn gl_attachment(self) -> u32 {
+        if self.has_stencil() {
+            glow::DEPTH_STENCIL_ATTACHMENT
+        } else {
+            glow::DEPTH_ATTACHMENT
+        }
+    }
+}
@@ -35,6 +35,7 @@
 //!         fragment_shader: r#" void main() {
 //!         gl_FragColor = frag_color;
 //!     }"#,
+//!         ..Default::default()
 //!     },
 //! )?;
 //!
@@ -80,27 +81,41 @@ type GlTexture = <glow::Context as HasContext>::Texture;
 type GlProgram = <glow::Context as HasContext>::Program;
 type GlShader = <glow::Context as HasContext>::Shader;
 type GlFramebuffer = <glow::Context as HasContext>::Framebuffer;
+type GlRenderbuffer = <glow::Context as HasContext>::Renderbuffer;
 type GlBuffer = <glow::Context as HasContext>::Buffer;
 type GlVertexArray = <glow::Context as HasContext>::VertexArray;
+type GlUniformLocation = <glow::Context as HasContext>::UniformLocation;
+type GlFence = <glow::Context as HasContext>::Fence;
+type GlSampler = <glow::Context as HasContext>::Sampler;
 
 mod attribute;
 mod buffer;
 mod context;
+mod readback;
+mod sampler;
 mod shader;
 mod surface;
 mod texture;
 mod uniform;
+mod uniform_buffer;
 
 pub mod blend;
+pub mod blit;
+#[cfg(feature = "containers")]
+pub mod container;
 pub mod depth;
+pub mod stencil;
 
-pub use self::attribute::{Attribute, AttributeType};
-pub use self::buffer::{Buffer, ElementBuffer, VertexBuffer};
+pub use self::attribute::{Attribute, AttributeType, ComponentType};
+pub use self::buffer::{Buffer, ElementBuffer, ElementIndex, Vertex, VertexBuffer};
 pub use self::context::Context;
-pub use self::shader::{ShaderDescription, ShaderProgram};
-pub use self::surface::Surface;
-pub use self::texture::{Texture, TextureFilter, TextureWrap};
+pub use self::readback::PixelReadback;
+pub use self::sampler::Sampler;
+pub use self::shader::{ShaderDescription, ShaderProgram, UniformHandle};
+pub use self::surface::{AttachmentError, MultisampleSurface, Surface};
+pub use self::texture::{CubeFace, Texture, TextureFilter, TextureKind, TextureWrap};
 pub use self::uniform::{Uniform, UniformType, UniformValue};
+pub use self::uniform_buffer::{UniformBlock, UniformBlockLayout, UniformBuffer};
 
 pub use glow;
 
@@ -109,6 +124,7 @@ pub(crate) enum Position {
     Output,
 }
 
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
 /// Used to determine whether shader uniforms are ints or floats
 pub enum NumberType {
     Int,
@@ -116,25 +132,286 @@ pub enum NumberType {
 }
 
 /// How a pixel's color is laid out in memory
+///
+/// The formats beyond `RGB` and `RGBA` store fewer channels or more precision per channel, for
+/// uses like HDR rendering, shadow maps, or textures that hold data rather than colors.
+///
+/// The floating-point formats can be uploaded from `f32` data with [`Texture::set_image_f32`],
+/// and the half-float formats from raw 16-bit halves with [`Texture::set_image_u16`]. On WebGL,
+/// the 32-bit float formats only support `Nearest` filtering unless `OES_texture_float_linear` is
+/// available, and the integer formats `R32UI` and `R32I` never support `Linear` filtering.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum ColorFormat {
     /// One red pixel byte, followed by one blue, and one green
     RGB,
     /// One red, blue, green, then alpha (transparency)
     RGBA,
+    /// One red byte
+    R8,
+    /// One red byte, followed by one green
+    RG8,
+    /// One red 16-bit float
+    R16F,
+    /// One red, green, blue, then alpha 16-bit float
+    RGBA16F,
+    /// One red 32-bit float
+    R32F,
+    /// One red, green, blue, then alpha 32-bit float
+    RGBA32F,
+    /// One red 32-bit unsigned integer, read in shaders with a [`UniformType::USampler2D`]
+    R32UI,
+    /// One red 32-bit signed integer, read in shaders with a [`UniformType::ISampler2D`]
+    R32I,
+    /// Like `RGB`, but the bytes are sRGB-encoded and converted to linear when sampled
+    SRGB,
+    /// Like `RGBA`, but the color bytes are sRGB-encoded and converted to linear when sampled
+    SRGBA,
 }
 
 impl ColorFormat {
     pub fn bytes_per_pixel(&self) -> u32 {
+        self.channels() * self.bytes_per_channel()
+    }
+
+    /// The number of bytes `layers` images of the given size take up when tightly packed, or
+    /// `None` if that doesn't fit in a `usize`
+    pub(crate) fn data_size(&self, width: u32, height: u32, layers: u32) -> Option<usize> {
+        (width as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(layers as usize)?
+            .checked_mul(self.bytes_per_pixel() as usize)
+    }
+
+    /// The number of color channels in each pixel
+    pub fn channels(&self) -> u32 {
+        use ColorFormat::*;
+        match self {
+            R8 | R16F | R32F | R32UI | R32I => 1,
+            RG8 => 2,
+            RGB | SRGB => 3,
+            RGBA | RGBA16F | RGBA32F | SRGBA => 4,
+        }
+    }
+
+    fn bytes_per_channel(&self) -> u32 {
+        use ColorFormat::*;
+        match self {
+            RGB | RGBA | R8 | RG8 | SRGB | SRGBA => 1,
+            R16F | RGBA16F => 2,
+            R32F | RGBA32F | R32UI | R32I => 4,
+        }
+    }
+
+    /// The format the texture is stored in on the GPU
+    pub(crate) fn gl_internal_format(&self) -> u32 {
+        use ColorFormat::*;
         match self {
-            ColorFormat::RGBA => 4,
-            ColorFormat::RGB => 3,
+            RGB => glow::RGB,
+            RGBA => glow::RGBA,
+            R8 => glow::R8,
+            RG8 => glow::RG8,
+            R16F => glow::R16F,
+            RGBA16F => glow::RGBA16F,
+            R32F => glow::R32F,
+            RGBA32F => glow::RGBA32F,
+            R32UI => glow::R32UI,
+            R32I => glow::R32I,
+            SRGB => glow::SRGB8,
+            SRGBA => glow::SRGB8_ALPHA8,
         }
     }
 
-    fn gl_format(&self) -> u32 {
+    /// The sized internal format, which renderbuffers require on OpenGL ES and WebGL
+    pub(crate) fn gl_sized_internal_format(&self) -> u32 {
         match self {
-            ColorFormat::RGB => glow::RGB,
-            ColorFormat::RGBA => glow::RGBA,
+            ColorFormat::RGB => glow::RGB8,
+            ColorFormat::RGBA => glow::RGBA8,
+            _ => self.gl_internal_format(),
+        }
+    }
+
+    /// The channels of the pixel data passed to or from the GPU
+    pub(crate) fn gl_format(&self) -> u32 {
+        use ColorFormat::*;
+        match self {
+            RGB | SRGB => glow::RGB,
+            RGBA | RGBA16F | RGBA32F | SRGBA => glow::RGBA,
+            R8 | R16F | R32F => glow::RED,
+            RG8 => glow::RG,
+            R32UI | R32I => glow::RED_INTEGER,
+        }
+    }
+
+    /// The type of each channel of the pixel data passed to or from the GPU
+    pub(crate) fn gl_type(&self) -> u32 {
+        use ColorFormat::*;
+        match self {
+            RGB | RGBA | R8 | RG8 | SRGB | SRGBA => glow::UNSIGNED_BYTE,
+            R16F | RGBA16F => glow::HALF_FLOAT,
+            R32F | RGBA32F => glow::FLOAT,
+            R32UI => glow::UNSIGNED_INT,
+            R32I => glow::INT,
+        }
+    }
+
+    /// If this format is stored as floats, and can be uploaded from `f32` data
+    pub(crate) fn is_float(&self) -> bool {
+        use ColorFormat::*;
+        matches!(self, R16F | RGBA16F | R32F | RGBA32F)
+    }
+
+    /// If mipmaps can be generated for this format on the current platform
+    ///
+    /// Desktop GL can generate mipmaps for any filterable format, which excludes integers.
+    pub(crate) fn can_generate_mipmaps(&self) -> bool {
+        if cfg!(target_arch = "wasm32") {
+            self.supports_mipmaps()
+        } else {
+            !matches!(self, ColorFormat::R32UI | ColorFormat::R32I)
+        }
+    }
+
+    /// If mipmaps can be generated for this format on every platform
+    ///
+    /// Generating mipmaps requires a format that is both filterable and renderable, which the
+    /// float, integer, and `SRGB` formats aren't guaranteed to be on WebGL.
+    pub(crate) fn supports_mipmaps(&self) -> bool {
+        use ColorFormat::*;
+        matches!(self, RGB | RGBA | R8 | RG8 | SRGBA)
+    }
+}
+
+/// A block-compressed texture format, uploaded with [`Texture::set_compressed_image`]
+///
+/// Compressed formats store blocks of pixels (4x4 for most) in a fixed number of bytes, and stay
+/// compressed in GPU memory. Each family depends on an extension, so check
+/// [`Context::supports_compressed_format`] or [`Context::compressed_formats`] before using one:
+/// the BC formats are common on desktop GPUs, while ETC2 and ASTC are common on mobile GPUs.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum CompressedFormat {
+    /// S3TC / DXT1 RGB, 8 bytes per 4x4 block
+    BC1,
+    /// S3TC / DXT1 RGB with 1-bit alpha, 8 bytes per 4x4 block
+    BC1A,
+    /// S3TC / DXT3 RGBA with explicit alpha, 16 bytes per 4x4 block
+    BC2,
+    /// S3TC / DXT5 RGBA with interpolated alpha, 16 bytes per 4x4 block
+    BC3,
+    /// RGTC one-channel red, 8 bytes per 4x4 block
+    BC4,
+    /// RGTC two-channel red and green, 16 bytes per 4x4 block
+    BC5,
+    /// BPTC RGBA, 16 bytes per 4x4 block
+    BC7,
+    /// Like `BC7`, but the color is sRGB-encoded and converted to linear when sampled
+    BC7SRGB,
+    /// ETC2 RGB, 8 bytes per 4x4 block
+    ETC2RGB,
+    /// ETC2 RGBA with EAC alpha, 16 bytes per 4x4 block
+    ETC2RGBA,
+    /// ASTC RGBA, 16 bytes per 4x4 block
+    ASTC4x4,
+    /// ASTC RGBA, 16 bytes per 6x6 block
+    ASTC6x6,
+    /// ASTC RGBA, 16 bytes per 8x8 block
+    ASTC8x8,
+}
+
+impl CompressedFormat {
+    /// Every compressed format, in declaration order
+    pub const ALL: [CompressedFormat; 13] = [
+        CompressedFormat::BC1,
+        CompressedFormat::BC1A,
+        CompressedFormat::BC2,
+        CompressedFormat::BC3,
+        CompressedFormat::BC4,
+        CompressedFormat::BC5,
+        CompressedFormat::BC7,
+        CompressedFormat::BC7SRGB,
+        CompressedFormat::ETC2RGB,
+        CompressedFormat::ETC2RGBA,
+        CompressedFormat::ASTC4x4,
+        CompressedFormat::ASTC6x6,
+        CompressedFormat::ASTC8x8,
+    ];
+
+    /// The width and height in pixels of each compressed block
+    pub fn block_size(&self) -> (u32, u32) {
+        use CompressedFormat::*;
+        match self {
+            ASTC6x6 => (6, 6),
+            ASTC8x8 => (8, 8),
+            _ => (4, 4),
+        }
+    }
+
+    /// The number of bytes each compressed block takes up
+    pub fn bytes_per_block(&self) -> u32 {
+        use CompressedFormat::*;
+        match self {
+            BC1 | BC1A | BC4 | ETC2RGB => 8,
+            _ => 16,
+        }
+    }
+
+    /// The number of bytes an image of the given size takes up, including partial blocks
+    ///
+    /// Returns `None` if the size doesn't fit in a `usize`.
+    pub fn data_size(&self, width: u32, height: u32) -> Option<usize> {
+        let (block_width, block_height) = self.block_size();
+        (width.div_ceil(block_width) as usize)
+            .checked_mul(height.div_ceil(block_height) as usize)?
+            .checked_mul(self.bytes_per_block() as usize)
+    }
+
+    pub(crate) fn gl_internal_format(&self) -> u32 {
+        use CompressedFormat::*;
+        match self {
+            BC1 => glow::COMPRESSED_RGB_S3TC_DXT1_EXT,
+            BC1A => glow::COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            BC2 => glow::COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            BC3 => glow::COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            BC4 => glow::COMPRESSED_RED_RGTC1,
+            BC5 => glow::COMPRESSED_RG_RGTC2,
+            BC7 => glow::COMPRESSED_RGBA_BPTC_UNORM,
+            BC7SRGB => glow::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            ETC2RGB => glow::COMPRESSED_RGB8_ETC2,
+            ETC2RGBA => glow::COMPRESSED_RGBA8_ETC2_EAC,
+            ASTC4x4 => glow::COMPRESSED_RGBA_ASTC_4x4_KHR,
+            ASTC6x6 => glow::COMPRESSED_RGBA_ASTC_6x6_KHR,
+            ASTC8x8 => glow::COMPRESSED_RGBA_ASTC_8x8_KHR,
+        }
+    }
+
+    /// The extensions which provide this format, any of which is enough
+    ///
+    /// An empty list means the format is part of the core API.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn extensions(&self) -> &'static [&'static str] {
+        use CompressedFormat::*;
+        match self {
+            BC1 | BC1A | BC2 | BC3 => &["EXT_texture_compression_s3tc"],
+            // RGTC is core since OpenGL 3.0
+            BC4 | BC5 => &[],
+            BC7 | BC7SRGB => &[
+                "ARB_texture_compression_bptc",
+                "EXT_texture_compression_bptc",
+            ],
+            ETC2RGB | ETC2RGBA => &["ARB_ES3_compatibility"],
+            ASTC4x4 | ASTC6x6 | ASTC8x8 => &["KHR_texture_compression_astc_ldr"],
+        }
+    }
+
+    /// The extensions which provide this format, any of which is enough
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn extensions(&self) -> &'static [&'static str] {
+        use CompressedFormat::*;
+        match self {
+            BC1 | BC1A | BC2 | BC3 => &["WEBGL_compressed_texture_s3tc"],
+            BC4 | BC5 => &["EXT_texture_compression_rgtc"],
+            BC7 | BC7SRGB => &["EXT_texture_compression_bptc"],
+            ETC2RGB | ETC2RGBA => &["WEBGL_compressed_texture_etc"],
+            ASTC4x4 | ASTC6x6 | ASTC8x8 => &["WEBGL_compressed_texture_astc"],
         }
     }
 }
@@ -197,14 +474,56 @@ pub enum GolemError {
     ///
     /// Shader operations include setting uniforms and drawing
     NotCurrentProgram,
-    /// A texture filter requiring mipmaps was used when mipmaps were unavailable
+    /// Mipmaps were required when they were unavailable
     ///
-    /// Mipmaps are only available for minification, and only for power-of-two sized textures (2x2, 4x4, etc.)
+    /// Mipmap filters are only available for minification, and mipmaps can't be generated for
+    /// some textures; see [`Texture::generate_mipmaps`].
     MipMapsUnavailable,
-    /// A wrap option was set for a Texture that isn't available
+    /// A wrap option was set that isn't available on this platform
     ///
-    /// Texture repeats are currently only supported for power-of-2 sized textures (2x2, 4x4, etc.)
+    /// Every wrap option is available for every texture, including textures whose sizes aren't
+    /// powers of 2, except [`TextureWrap::ClampToBorder`] on the web.
     IllegalWrapOption,
+    /// A uniform of a type that can't be stored in a uniform buffer was put in a [`UniformBlock`]
+    ///
+    /// Samplers and user-defined types can't be members of a uniform block
+    IllegalUniformBlockMember(String),
+    /// A uniform value was provided that doesn't match the type the uniform was declared with
+    ///
+    /// The types are given by their names in GLSL
+    UniformTypeMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// More textures were bound for one draw than the GPU has texture units
+    ///
+    /// The maximum number of textures is given, which is one less than
+    /// [`glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS`] because unit 0 is reserved by golem.
+    TooManyTextures(u32),
+    /// A texture had a different format than an operation required
+    ///
+    /// This is returned by [`MultisampleSurface::resolve_into`], which can't convert between
+    /// formats.
+    FormatMismatch {
+        expected: ColorFormat,
+        actual: ColorFormat,
+    },
+    /// A [`Surface`] can't be rendered to with its current attachments
+    ///
+    /// The reason is given by the name of the GL framebuffer status, like
+    /// `FRAMEBUFFER_INCOMPLETE_ATTACHMENT` for an attachment with a size of 0 or a format that
+    /// can't be rendered to.
+    IncompleteFramebuffer(&'static str),
+    /// The named GL extension is required, but the context doesn't support it
+    ExtensionUnavailable(&'static str),
+    /// Image data given to a texture didn't match its size and format, with the reason
+    InvalidImageData(String),
+    /// A texture file couldn't be parsed or isn't supported, with the reason
+    ///
+    /// This is only returned by the [`container`](crate::container) module, which requires the
+    /// `containers` feature.
+    InvalidTextureFile(String),
 }
 
 impl From<String> for GolemError {
@@ -222,8 +541,72 @@ impl Display for GolemError {
             GolemError::NotCurrentProgram => write!(fmt, "Shader program not bound"),
             GolemError::MipMapsUnavailable => write!(fmt, "Mipmaps are unavailable"),
             GolemError::IllegalWrapOption => write!(fmt, "An illegal texture wrap"),
+            GolemError::IllegalUniformBlockMember(e) => {
+                write!(fmt, "Illegal uniform block member: {}", e)
+            }
+            GolemError::TooManyTextures(max) => {
+                write!(fmt, "Too many textures bound; the maximum is {}", max)
+            }
+            GolemError::FormatMismatch { expected, actual } => write!(
+                fmt,
+                "Expected a texture with the format {:?}, but it was {:?}",
+                expected, actual
+            ),
+            GolemError::IncompleteFramebuffer(e) => write!(fmt, "Incomplete framebuffer: {}", e),
+            GolemError::ExtensionUnavailable(e) => write!(fmt, "Extension unavailable: {}", e),
+            GolemError::InvalidImageData(e) => write!(fmt, "Invalid image data: {}", e),
+            GolemError::InvalidTextureFile(e) => write!(fmt, "Invalid texture file: {}", e),
+            GolemError::UniformTypeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                fmt,
+                "Uniform {} was declared as {}, but given {}",
+                name, expected, actual
+            ),
         }
     }
 }
 
 impl std::error::Error for GolemError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_sizes_count_whole_blocks() {
+        assert_eq!(CompressedFormat::BC1.data_size(4, 4), Some(8));
+        assert_eq!(CompressedFormat::BC3.data_size(4, 4), Some(16));
+        assert_eq!(
+            CompressedFormat::BC7.data_size(256, 128),
+            Some(64 * 32 * 16)
+        );
+        assert_eq!(
+            CompressedFormat::ASTC6x6.data_size(12, 18),
+            Some(2 * 3 * 16)
+        );
+    }
+
+    #[test]
+    fn compressed_sizes_round_up_partial_blocks() {
+        // Mipmap levels smaller than a block still take up a whole block
+        assert_eq!(CompressedFormat::BC1.data_size(1, 1), Some(8));
+        assert_eq!(CompressedFormat::BC4.data_size(5, 3), Some(2 * 8));
+        assert_eq!(CompressedFormat::ASTC8x8.data_size(9, 17), Some(2 * 3 * 16));
+    }
+
+    #[test]
+    fn color_sizes_that_overflow_are_none() {
+        assert_eq!(ColorFormat::RG8.data_size(3, 3, 2), Some(36));
+        // 65536 * 65536 * 4 wraps to 0 as a u32
+        assert_ne!(ColorFormat::RGBA.data_size(65536, 65536, 1), Some(0));
+        assert_eq!(ColorFormat::RGBA32F.data_size(u32::MAX, u32::MAX, 2), None);
+    }
+
+    #[test]
+    fn compressed_sizes_that_overflow_are_none() {
+        assert_eq!(CompressedFormat::BC7.data_size(u32::MAX, u32::MAX), None);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::*;
+
+/// A transfer of pixels from a framebuffer to the CPU, which finishes in the background
+///
+/// Reading pixels with [`Surface::get_pixel_data`] waits for all rendering to finish, stalling
+/// the GPU. A readback instead copies the pixels into a buffer on the GPU, so they can be read
+/// a frame or two later without waiting. Start one with [`PixelReadback::start`], then call
+/// [`PixelReadback::try_read`] (or check [`PixelReadback::is_ready`]) on later frames.
+///
+/// ```rust
+/// # use golem::*;
+/// # fn func(ctx: &Context) -> Result<(), GolemError> {
+/// let readback = PixelReadback::start(ctx, None, 0, 0, 640, 480, ColorFormat::RGBA)?;
+/// // ... render the next frame ...
+/// let mut pixels = vec![0; readback.size()];
+/// if readback.try_read(&mut pixels) {
+///     // The pixels of the earlier frame are now in `pixels`
+/// }
+/// # Ok(()) }
+/// ```
+pub struct PixelReadback {
+    ctx: Context,
+    buffer: GlBuffer,
+    fence: GlFence,
+    size: usize,
+}
+
+impl PixelReadback {
+    /// Start copying a region of pixels from a surface, or the window if `None`
+    ///
+    /// The ColorFormat determines how many bytes each pixel is, given by
+    /// [`ColorFormat::bytes_per_pixel`]. Rows of pixels are tightly packed, starting from the
+    /// bottom row.
+    ///
+    /// The region must fit in a GPU buffer (at most `i32::MAX` bytes), which is checked for via
+    /// an assert.
+    pub fn start(
+        ctx: &Context,
+        source: Option<&Surface>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        format: ColorFormat,
+    ) -> Result<PixelReadback, GolemError> {
+        let size = format
+            .data_size(width, height, 1)
+            .filter(|&size| size <= i32::MAX as usize)
+            .expect("The readback region was too large to fit in a buffer");
+        let gl = &ctx.0.gl;
+        unsafe {
+            let buffer = gl.create_buffer()?;
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(buffer));
+            gl.buffer_data_size(glow::PIXEL_PACK_BUFFER, size as i32, glow::STREAM_READ);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, source.map(|surface| surface.id));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glReadPixels.xhtml
+            // With a pixel pack buffer bound, the pixels are written to the buffer at the offset
+            // instead of to client memory. The buffer was just sized to hold the whole region, and
+            // rows are tightly packed because the context sets PACK_ALIGNMENT to 1.
+            gl.read_pixels(
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format.gl_format(),
+                format.gl_type(),
+                glow::PixelPackData::BufferOffset(0),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *ctx.0.current_surface.borrow());
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glFenceSync.xhtml
+            // The fence is signaled once the copy above (and everything before it) is complete
+            // If it can't be created, the buffer would otherwise leak, because it's only deleted
+            // when a PixelReadback is dropped
+            let fence = match gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) {
+                Ok(fence) => fence,
+                Err(error) => {
+                    gl.delete_buffer(buffer);
+                    return Err(error.into());
+                }
+            };
+            gl.flush();
+
+            Ok(PixelReadback {
+                ctx: Context(ctx.0.clone()),
+                buffer,
+                fence,
+                size,
+            })
+        }
+    }
+
+    /// The number of bytes the readback holds
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the transfer is complete, so the pixels can be read without waiting
+    pub fn is_ready(&self) -> bool {
+        unsafe { self.ctx.0.gl.get_sync_status(self.fence) == glow::SIGNALED }
+    }
+
+    /// Copy the pixels into `data` if the transfer is complete, returning whether it was
+    ///
+    /// The data needs to hold at least [`PixelReadback::size`] bytes (which is checked for via an
+    /// `assert!`.) If the transfer isn't complete, the data is left untouched and this returns
+    /// `false`; try again on a later frame.
+    pub fn try_read(&self, data: &mut [u8]) -> bool {
+        assert!(
+            data.len() >= self.size,
+            "The buffer was not large enough to hold the data"
+        );
+        if !self.is_ready() {
+            return false;
+        }
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(self.buffer));
+            gl.get_buffer_sub_data(glow::PIXEL_PACK_BUFFER, 0, &mut data[..self.size]);
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+        }
+
+        true
+    }
+}
+
+impl Drop for PixelReadback {
+    fn drop(&mut self) {
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.delete_sync(self.fence);
+            gl.delete_buffer(self.buffer);
+        }
+    }
+}
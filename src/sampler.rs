@@ -0,0 +1,186 @@
+use crate::depth::DepthTestFunction;
+use crate::*;
+use core::num::NonZeroU32;
+
+/// A set of sampling options, used in place of the options stored on a [`Texture`]
+///
+/// While a sampler is bound to a texture unit, textures on that unit are filtered and wrapped
+/// according to the sampler, rather than their own settings from [`Texture::set_minification`]
+/// and friends. This allows one texture to be sampled in different ways by different samplers
+/// in the same draw call.
+///
+/// ```rust
+/// # use golem::*;
+/// # fn func(ctx: &Context, shader: &ShaderProgram, texture: &Texture) -> Result<(), GolemError> {
+/// let pixelated = Sampler::new(ctx)?;
+/// pixelated.set_minification(TextureFilter::Nearest);
+/// pixelated.set_magnification(TextureFilter::Nearest)?;
+/// shader.bind_texture("image", texture)?;
+/// shader.bind_sampler("image", Some(&pixelated))?;
+/// # Ok(()) }
+/// ```
+pub struct Sampler {
+    ctx: Context,
+    id: GlSampler,
+}
+
+impl Sampler {
+    /// Create a new sampler, with the GL defaults
+    ///
+    /// The defaults are `NearestMipmapLinear` minification, `Linear` magnification, and `Repeat`
+    /// wrapping. Samplers require OpenGL 3.3 or the `ARB_sampler_objects` extension on desktop,
+    /// and this returns [`GolemError::ExtensionUnavailable`] without them.
+    pub fn new(ctx: &Context) -> Result<Sampler, GolemError> {
+        if !ctx.has_feature(3, 3, "ARB_sampler_objects") {
+            return Err(GolemError::ExtensionUnavailable("ARB_sampler_objects"));
+        }
+        let ctx = Context(ctx.0.clone());
+        let id = unsafe { ctx.0.gl.create_sampler() }?;
+
+        Ok(Sampler { ctx, id })
+    }
+
+    /// Bind the sampler to a texture unit, where it replaces the sampling options of the texture
+    ///
+    /// The unit is the same `bind_point` passed to [`Texture::set_active`]. The value 0 is
+    /// reserved by `golem`, so it cannot be passed to this function.
+    ///
+    /// [`ShaderProgram::bind_sampler`] chooses the unit from the name of the sampler uniform.
+    pub fn bind(&self, bind_point: NonZeroU32) {
+        self.bind_to_unit(bind_point.get());
+    }
+
+    /// Unbind any sampler from a texture unit, so the texture's own sampling options apply
+    pub fn unbind(ctx: &Context, bind_point: NonZeroU32) {
+        unsafe {
+            ctx.0.gl.bind_sampler(bind_point.get(), None);
+        }
+    }
+
+    pub(crate) fn bind_to_unit(&self, unit: u32) {
+        // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glBindSampler.xhtml
+        // The unit comes from a texture unit allocation, so it's below the maximum
+        unsafe {
+            self.ctx.0.gl.bind_sampler(unit, Some(self.id));
+        }
+    }
+
+    fn set_param(&self, param: u32, value: i32) {
+        unsafe {
+            self.ctx.0.gl.sampler_parameter_i32(self.id, param, value);
+        }
+    }
+
+    fn set_param_f32(&self, param: u32, value: f32) {
+        unsafe {
+            self.ctx.0.gl.sampler_parameter_f32(self.id, param, value);
+        }
+    }
+
+    /// Determine how textures should scale down
+    ///
+    /// Filters that use mipmaps only work for textures that have them.
+    pub fn set_minification(&self, min: TextureFilter) {
+        self.set_param(glow::TEXTURE_MIN_FILTER, min.to_gl());
+    }
+
+    /// Determine how textures should scale up
+    pub fn set_magnification(&self, max: TextureFilter) -> Result<(), GolemError> {
+        if max.uses_mipmap() {
+            Err(GolemError::MipMapsUnavailable)
+        } else {
+            self.set_param(glow::TEXTURE_MAG_FILTER, max.to_gl());
+            Ok(())
+        }
+    }
+
+    /// Determine how textures are wrapped horizontally
+    ///
+    /// The errors are the same as [`Texture::set_wrap_h`].
+    pub fn set_wrap_h(&self, wrap: TextureWrap) -> Result<(), GolemError> {
+        self.set_param(glow::TEXTURE_WRAP_S, wrap.to_gl()?);
+        Ok(())
+    }
+
+    /// Determine how textures are wrapped vertically
+    ///
+    /// The errors are the same as [`Texture::set_wrap_h`].
+    pub fn set_wrap_v(&self, wrap: TextureWrap) -> Result<(), GolemError> {
+        self.set_param(glow::TEXTURE_WRAP_T, wrap.to_gl()?);
+        Ok(())
+    }
+
+    /// Determine how 3D textures are wrapped in depth, and how cube maps are wrapped
+    ///
+    /// The errors are the same as [`Texture::set_wrap_h`].
+    pub fn set_wrap_depth(&self, wrap: TextureWrap) -> Result<(), GolemError> {
+        self.set_param(glow::TEXTURE_WRAP_R, wrap.to_gl()?);
+        Ok(())
+    }
+
+    /// Limit which mipmap levels are used, from `min` (the most detailed) to `max`
+    ///
+    /// The defaults are `-1000.0` and `1000.0`, i.e. "all levels are used".
+    pub fn set_lod_range(&self, min: f32, max: f32) {
+        assert!(min <= max, "The minimum LOD was above the maximum");
+        self.set_param_f32(glow::TEXTURE_MIN_LOD, min);
+        self.set_param_f32(glow::TEXTURE_MAX_LOD, max);
+    }
+
+    /// Sample textures more times when they're viewed at a steep angle, up to `level` times
+    ///
+    /// The errors and limits are the same as [`Texture::set_anisotropy`].
+    pub fn set_anisotropy(&self, level: f32) -> Result<(), GolemError> {
+        self.ctx.check_anisotropy(level)?;
+        self.set_param_f32(glow::TEXTURE_MAX_ANISOTROPY_EXT, level);
+
+        Ok(())
+    }
+
+    /// Offset the mipmap level chosen when sampling, where positive values are blurrier
+    ///
+    /// LOD bias is only available on desktop.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_lod_bias(&self, bias: f32) {
+        self.set_param_f32(glow::TEXTURE_LOD_BIAS, bias);
+    }
+
+    /// Compare depth textures against a reference value instead of returning their value
+    ///
+    /// With a function set, sampling a depth texture through a [`UniformType::Sampler2DShadow`]
+    /// returns how many of the samples pass the comparison, which is useful for shadow maps.
+    /// `None` returns the depth values directly, which is the default.
+    pub fn set_compare_mode(&self, function: Option<DepthTestFunction>) {
+        match function {
+            Some(function) => {
+                self.set_param(
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                self.set_param(glow::TEXTURE_COMPARE_FUNC, function.to_gl() as i32);
+            }
+            None => self.set_param(glow::TEXTURE_COMPARE_MODE, glow::NONE as i32),
+        }
+    }
+
+    /// Set the color used by [`TextureWrap::ClampToBorder`], as RGBA
+    ///
+    /// Border colors are only available on desktop.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_border_color(&self, color: [f32; 4]) {
+        unsafe {
+            self.ctx
+                .0
+                .gl
+                .sampler_parameter_f32_slice(self.id, glow::TEXTURE_BORDER_COLOR, &color);
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            self.ctx.0.gl.delete_sampler(self.id);
+        }
+    }
+}
@@ -1,23 +1,49 @@
 use crate::*;
+use core::cell::RefCell;
 use core::mem::size_of;
 use core::ops::Range;
 use std::borrow::ToOwned;
+use std::format;
 use std::vec::Vec;
 
 /// The parameters to create a [`ShaderProgram`]
+///
+/// Every field defaults to empty, so the optional ones can be left out with
+/// `..Default::default()`.
+#[derive(Default)]
 pub struct ShaderDescription<'a> {
     /// The inputs to the vertex shader stage, which are also the inputs to the whole shader
+    ///
+    /// The attributes are read from the vertex buffer in order, laid out like the fields of a
+    /// `#[repr(C)]` struct (see [`Vertex`])
     pub vertex_input: &'a [Attribute],
+    /// The per-instance inputs to the vertex shader stage, used by instanced draw calls
+    ///
+    /// Each instance of the geometry reads the next set of these values, rather than each vertex.
+    /// They are provided from their own buffer, see [`ShaderProgram::draw_instanced`]. Leave this
+    /// empty if the shader is not used for instanced drawing.
+    pub instance_input: &'a [Attribute],
     /// The inputs to the fragment shader stage, which are also the outputs from the vertex shader
     pub fragment_input: &'a [Attribute],
+    /// The named outputs of the fragment shader stage, for rendering to several textures at once
+    ///
+    /// Each output is written to the color attachment of the bound [`Surface`] at the same index
+    /// (see [`Surface::with_textures`]). Leave this empty to write a single color to
+    /// `gl_FragColor` instead.
+    pub fragment_output: &'a [Attribute],
     /// The uniform values available to all shader stages, across all vertices of a draw call
     ///
     /// Uniforms can be bound with [`ShaderProgram::set_uniform`]
     pub uniforms: &'a [Uniform],
+    /// The groups of uniform values read from [`UniformBuffer`]s, available to all shader stages
+    ///
+    /// Blocks with the same name share the same buffer across every shader program, see
+    /// [`UniformBuffer::bind`]
+    pub uniform_blocks: &'a [UniformBlock<'a>],
     /// The text of the vertex shader stage
     ///
     /// Do not include the vertex inputs, outputs, or uniforms, use the [`vertex_input`],
-    /// [`fragment_input`], and [`uniforms`] fields instead. Just provide the 'main' function, as
+    /// [`fragment_input`], [`uniforms`], and [`uniform_blocks`] fields instead. Just provide the 'main' function, as
     /// well as any helpers. The shader inputs, outputs, and uniforms will be generated for you.
     ///
     /// The inputs to this stage are defined as the [`vertex_input`] and the ouptuts are the
@@ -26,16 +52,18 @@ pub struct ShaderDescription<'a> {
     /// [`vertex_input`]: ShaderDescription::vertex_input
     /// [`fragment_input`]: ShaderDescription::fragment_input
     /// [`uniforms`]: ShaderDescription::uniforms
+    /// [`uniform_blocks`]: ShaderDescription::uniform_blocks
     pub vertex_shader: &'a str,
     /// The text of the fragment shader stage
     ///
     /// See the documentation of the [`vertex_shader`]. The inputs to this stage are
     /// defined as the [`fragment_input`] and the ouptut is `gl_FragColor`, a vec4 that represents
-    /// the RGBA color of the fragment. Use the function `texture` to read values from GLSL
-    /// textures; it will be converted to `texture2D` on the web backend.
+    /// the RGBA color of the fragment, or the [`fragment_output`] if there are any. Use the
+    /// function `texture` to read values from GLSL textures of any kind.
     ///
     /// [`vertex_shader`]: ShaderDescription::vertex_shader
     /// [`fragment_input`]: ShaderDescription::fragment_input
+    /// [`fragment_output`]: ShaderDescription::fragment_output
     pub fragment_shader: &'a str,
 }
 
@@ -46,6 +74,29 @@ pub struct ShaderProgram {
     vertex: GlShader,
     fragment: GlShader,
     input: Vec<Attribute>,
+    instance_input: Vec<Attribute>,
+    element_type: RefCell<(u32, usize)>,
+    uniforms: Vec<DeclaredUniform>,
+    texture_units: RefCell<Vec<&'static str>>,
+    /// The texture units with a sampler bound by bind_sampler for the next draw
+    sampler_units: RefCell<Vec<u32>>,
+}
+
+/// A uniform from the [`ShaderDescription`], with its location found at link time
+struct DeclaredUniform {
+    name: &'static str,
+    u_type: UniformType,
+    location: Option<GlUniformLocation>,
+}
+
+/// A uniform of a [`ShaderProgram`], found ahead of time to avoid looking it up by name
+///
+/// Handles are created by [`ShaderProgram::uniform`], and used by
+/// [`ShaderProgram::set_uniform_handle`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UniformHandle {
+    program: GlProgram,
+    index: usize,
 }
 
 fn generate_shader_text(
@@ -54,27 +105,65 @@ fn generate_shader_text(
     inputs: &[Attribute],
     outputs: &[Attribute],
     uniforms: &[Uniform],
+    uniform_blocks: &[UniformBlock],
 ) -> String {
     let mut shader = String::new();
 
     #[cfg(not(target_arch = "wasm32"))]
     shader.push_str("#version 150\n");
 
+    // WebGL 2 uses GLSL ES 3.00, which has no default precision for the layered, integer, and
+    // shadow samplers; integer textures hold 32-bit values, so they need the highest precision
+    #[cfg(target_arch = "wasm32")]
+    shader.push_str(concat!(
+        "#version 300 es\n",
+        "precision mediump sampler2DArray;\n",
+        "precision mediump sampler3D;\n",
+        "precision highp usampler2D;\n",
+        "precision highp isampler2D;\n",
+        "precision mediump sampler2DShadow;\n",
+    ));
+
     shader.push_str("precision mediump float;\n");
     for attr in inputs.iter() {
         attr.as_glsl(is_vertex, Position::Input, &mut shader);
     }
-    for attr in outputs.iter() {
+    for (index, attr) in outputs.iter().enumerate() {
+        // WebGL has no glBindFragDataLocation, so each color output is placed in the shader
+        if !is_vertex && cfg!(target_arch = "wasm32") {
+            shader.push_str(&format!("layout(location = {}) ", index));
+        }
         attr.as_glsl(is_vertex, Position::Output, &mut shader);
     }
     for uniform in uniforms.iter() {
         uniform.as_glsl(&mut shader);
     }
+    for block in uniform_blocks.iter() {
+        block.as_glsl(&mut shader);
+    }
     shader.push_str(body);
 
     shader
 }
 
+fn align_to(offset: i32, alignment: i32) -> i32 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// The size in bytes of one interleaved set of the given attributes
+///
+/// The attributes are laid out like the fields of a `#[repr(C)]` struct, see [`Vertex`]
+fn attribute_stride(attributes: &[Attribute]) -> i32 {
+    let mut size = 0;
+    let mut alignment = 1;
+    for attr in attributes.iter() {
+        size = align_to(size, attr.alignment()) + attr.byte_size();
+        alignment = alignment.max(attr.alignment());
+    }
+
+    align_to(size, alignment)
+}
+
 impl ShaderProgram {
     /// Create a shader program with the given [`ShaderDescription`]
     pub fn new(ctx: &Context, desc: ShaderDescription) -> Result<ShaderProgram, GolemError> {
@@ -85,12 +174,19 @@ impl ShaderProgram {
             // 1. An error occurred creating the shader (handled by glow's error layer)
             // 2. An invalid value was passed (VERTEX_SHADER is valid)
             let vertex = gl.create_shader(glow::VERTEX_SHADER)?;
+            let vertex_inputs: Vec<Attribute> = desc
+                .vertex_input
+                .iter()
+                .chain(desc.instance_input.iter())
+                .cloned()
+                .collect();
             let vertex_source = generate_shader_text(
                 true,
                 desc.vertex_shader,
-                desc.vertex_input,
+                &vertex_inputs,
                 desc.fragment_input,
                 desc.uniforms,
+                desc.uniform_blocks,
             );
             log::debug!("Vertex shader source: {}", vertex_source);
             // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glShaderSource.xhtml
@@ -110,20 +206,19 @@ impl ShaderProgram {
 
             // For GL pre/post condition explanations, see vertex shader compilation above
             let fragment = gl.create_shader(glow::FRAGMENT_SHADER)?;
-            // Handle creating the output color and giving it a name, but only on desktop gl
-            #[cfg(target_arch = "wasm32")]
-            let (fragment_output, fragment_body) =
-                { (&[], &desc.fragment_shader.replace("texture", "texture2D")) };
-
-            #[cfg(not(target_arch = "wasm32"))]
-            let (fragment_output, fragment_body) = {
+            // Handle creating the output color and giving it a name, because GLSL 3 has no
+            // gl_FragColor
+            let default_output = [Attribute::new(
+                "outputColor",
+                AttributeType::Vector(Dimension::D4),
+            )];
+            let (fragment_output, fragment_body) = if desc.fragment_output.is_empty() {
                 (
-                    &[Attribute::new(
-                        "outputColor",
-                        AttributeType::Vector(Dimension::D4),
-                    )],
+                    &default_output[..],
                     &desc.fragment_shader.replace("gl_FragColor", "outputColor"),
                 )
+            } else {
+                (desc.fragment_output, &desc.fragment_shader.to_owned())
             };
             let fragment_source = generate_shader_text(
                 false,
@@ -131,6 +226,7 @@ impl ShaderProgram {
                 desc.fragment_input,
                 fragment_output,
                 desc.uniforms,
+                desc.uniform_blocks,
             );
             log::debug!("Fragment shader source: {}", fragment_source);
             gl.shader_source(fragment, &fragment_source);
@@ -153,16 +249,21 @@ impl ShaderProgram {
             gl.attach_shader(id, vertex);
             gl.attach_shader(id, fragment);
 
-            // Bind the color output for desktop GL
+            // Bind the color outputs for desktop GL, each to the draw buffer at its index (WebGL
+            // declares their locations in the shader instead)
             // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBindFragDataLocation.xhtml
             // Errors:
-            // 1. colorNumber will always be 0, and therefore cannot overrun the bounds
-            // 2. 'outputColor' does not started with the reserved 'gl_' prefix
+            // 1. colorNumber can overrun MAX_DRAW_BUFFERS if too many outputs are declared, which
+            //    is reported as a link failure below
+            // 2. Output names starting with the reserved 'gl_' prefix fail to compile above
             // 3. 'id' is generated by create_program above
             #[cfg(not(target_arch = "wasm32"))]
-            gl.bind_frag_data_location(id, 0, "outputColor");
+            for (index, output) in fragment_output.iter().enumerate() {
+                gl.bind_frag_data_location(id, index as u32, output.name());
+            }
 
-            for (index, attr) in desc.vertex_input.iter().enumerate() {
+            // Instance attributes are located directly after the vertex attributes
+            for (index, attr) in vertex_inputs.iter().enumerate() {
                 gl.bind_attrib_location(id, index as u32, attr.name());
             }
 
@@ -174,12 +275,39 @@ impl ShaderProgram {
             }
             log::trace!("Linked shader program succesfully");
 
+            // Point each uniform block at the binding shared by all blocks with its name
+            // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glUniformBlockBinding.xhtml
+            // Blocks that are unused by the shader may be optimized out, so they have no index
+            for block in desc.uniform_blocks.iter() {
+                if let Some(index) = gl.get_uniform_block_index(id, block.name) {
+                    let binding = ctx.uniform_block_binding(block.name)?;
+                    gl.uniform_block_binding(id, index, binding);
+                }
+            }
+
+            // Look up the declared uniforms once, rather than on every call to set_uniform
+            // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetUniformLocation.xhtml
+            let uniforms = desc
+                .uniforms
+                .iter()
+                .map(|uniform| DeclaredUniform {
+                    name: uniform.name,
+                    u_type: uniform.u_type.clone(),
+                    location: gl.get_uniform_location(id, uniform.name),
+                })
+                .collect();
+
             Ok(ShaderProgram {
                 ctx: Context(ctx.0.clone()),
                 id,
                 vertex,
                 fragment,
                 input: desc.vertex_input.to_vec(),
+                instance_input: desc.instance_input.to_vec(),
+                element_type: RefCell::new((glow::UNSIGNED_INT, size_of::<u32>())),
+                uniforms,
+                texture_units: RefCell::new(Vec::new()),
+                sampler_units: RefCell::new(Vec::new()),
             })
         }
     }
@@ -193,37 +321,262 @@ impl ShaderProgram {
     }
 
     /// Set a uniform value, assuming the shader is bound by [`ShaderProgram::bind`]
+    ///
+    /// Uniforms declared in the [`ShaderDescription`] use the location cached when the program
+    /// was linked. Other names (like a single element of an array, `lights[3]`) are looked up in
+    /// the program each time. To avoid the name lookup entirely, see [`ShaderProgram::uniform`].
+    ///
+    /// The value is checked against the declared [`UniformType`], returning
+    /// [`GolemError::UniformTypeMismatch`] if they disagree. Elements of declared arrays are
+    /// checked against the element type, and against the length of the array.
     pub fn set_uniform(&self, name: &str, uniform: UniformValue) -> Result<(), GolemError> {
         if self.is_bound() {
-            let gl = &self.ctx.0.gl;
-            let location = unsafe { gl.get_uniform_location(self.id, name) };
-            if location.is_none() {
-                return Err(GolemError::NoSuchUniform(name.to_owned()));
+            let queried;
+            let location = match self.uniforms.iter().find(|u| u.name == name) {
+                Some(declared) => {
+                    declared.u_type.check_value(name, &uniform)?;
+                    declared.location.as_ref()
+                }
+                None => {
+                    self.check_array_element(name, &uniform)?;
+                    queried = unsafe { self.ctx.0.gl.get_uniform_location(self.id, name) };
+                    queried.as_ref()
+                }
+            };
+            match location {
+                Some(location) => {
+                    self.upload_uniform(location, uniform);
+                    Ok(())
+                }
+                None => Err(GolemError::NoSuchUniform(name.to_owned())),
             }
-            use UniformValue::*;
-            let location = location.as_ref();
-            unsafe {
-                match uniform {
-                    Int(x) => gl.uniform_1_i32(location, x),
-                    IVector2([x, y]) => gl.uniform_2_i32(location, x, y),
-                    IVector3([x, y, z]) => gl.uniform_3_i32(location, x, y, z),
-                    IVector4([x, y, z, w]) => gl.uniform_4_i32(location, x, y, z, w),
-                    Float(x) => gl.uniform_1_f32(location, x),
-                    Vector2([x, y]) => gl.uniform_2_f32(location, x, y),
-                    Vector3([x, y, z]) => gl.uniform_3_f32(location, x, y, z),
-                    Vector4([x, y, z, w]) => gl.uniform_4_f32(location, x, y, z, w),
-                    Matrix2(mat) => gl.uniform_matrix_2_f32_slice(location, false, &mat),
-                    Matrix3(mat) => gl.uniform_matrix_3_f32_slice(location, false, &mat),
-                    Matrix4(mat) => gl.uniform_matrix_4_f32_slice(location, false, &mat),
+        } else {
+            Err(GolemError::NotCurrentProgram)
+        }
+    }
+
+    /// If the name is an element of a declared array (`name[index]`), check it against the array
+    fn check_array_element(&self, name: &str, uniform: &UniformValue) -> Result<(), GolemError> {
+        let element = name
+            .strip_suffix(']')
+            .and_then(|name| name.split_once('['))
+            .and_then(|(array, index)| Some((array, index.parse::<usize>().ok()?)));
+        let (array, index) = match element {
+            Some(element) => element,
+            None => return Ok(()),
+        };
+        match self.uniforms.iter().find(|u| u.name == array) {
+            Some(DeclaredUniform {
+                u_type: UniformType::Array(u_type, length),
+                ..
+            }) => {
+                if index >= *length {
+                    Err(GolemError::NoSuchUniform(name.to_owned()))
+                } else if uniform.array_len().is_some() {
+                    // Array values set the elements from the index onwards, so they must fit
+                    UniformType::Array(u_type.clone(), length - index).check_value(name, uniform)
+                } else {
+                    u_type.check_value(name, uniform)
                 }
             }
+            _ => Ok(()),
+        }
+    }
+
+    /// Find a uniform declared in the [`ShaderDescription`], to set it without looking it up by
+    /// name
+    ///
+    /// Uniforms that aren't used by the shader may be removed when it is compiled, in which case
+    /// they have no handle.
+    pub fn uniform(&self, name: &str) -> Result<UniformHandle, GolemError> {
+        self.uniforms
+            .iter()
+            .position(|u| u.name == name && u.location.is_some())
+            .map(|index| UniformHandle {
+                program: self.id,
+                index,
+            })
+            .ok_or_else(|| GolemError::NoSuchUniform(name.to_owned()))
+    }
 
+    /// Set a uniform value by its handle, assuming the shader is bound by [`ShaderProgram::bind`]
+    ///
+    /// The handle must have come from this program (which is checked for via an `assert!`), see
+    /// [`ShaderProgram::uniform`]. The value is checked against the declared type, like
+    /// [`ShaderProgram::set_uniform`].
+    pub fn set_uniform_handle(
+        &self,
+        handle: UniformHandle,
+        uniform: UniformValue,
+    ) -> Result<(), GolemError> {
+        assert!(
+            handle.program == self.id,
+            "The uniform handle belongs to a different shader program"
+        );
+        if self.is_bound() {
+            let declared = &self.uniforms[handle.index];
+            declared.u_type.check_value(declared.name, &uniform)?;
+            if let Some(location) = &declared.location {
+                self.upload_uniform(location, uniform);
+            }
             Ok(())
         } else {
             Err(GolemError::NotCurrentProgram)
         }
     }
 
+    /// Bind a texture to a sampler uniform, assuming the shader is bound by
+    /// [`ShaderProgram::bind`]
+    ///
+    /// Each sampler of the program is given its own texture unit the first time a texture is
+    /// bound to it, and the uniform is set to that unit. This replaces calling
+    /// [`Texture::set_active`] and setting the uniform separately. The units are handed out
+    /// again after every draw, so bind each texture a draw uses before drawing.
+    ///
+    /// The uniform must be declared in the [`ShaderDescription`] as the sampler type matching the
+    /// texture: a [`UniformType::Sampler2D`] for 2D textures ([`UniformType::USampler2D`] or
+    /// [`UniformType::ISampler2D`] for the integer formats, and either a [`UniformType::Sampler2D`]
+    /// or a [`UniformType::Sampler2DShadow`] for depth textures), or a
+    /// [`UniformType::SamplerCube`] for cube maps. Otherwise, this returns [`GolemError::NoSuchUniform`] or
+    /// [`GolemError::UniformTypeMismatch`]. If the program has more samplers than the GPU has
+    /// texture units (given by [`glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS`]), this returns
+    /// [`GolemError::TooManyTextures`].
+    pub fn bind_texture(&self, name: &str, texture: &Texture) -> Result<(), GolemError> {
+        if !self.is_bound() {
+            return Err(GolemError::NotCurrentProgram);
+        }
+        let declared = self
+            .uniforms
+            .iter()
+            .find(|u| u.name == name)
+            .ok_or_else(|| GolemError::NoSuchUniform(name.to_owned()))?;
+        let sampler = texture.sampler_type();
+        let is_shadow = declared.u_type == UniformType::Sampler2DShadow
+            && sampler == UniformType::Sampler2D
+            && texture.depth_format().is_some();
+        if declared.u_type != sampler && !is_shadow {
+            return Err(GolemError::UniformTypeMismatch {
+                name: name.to_owned(),
+                expected: declared.u_type.glsl_name(),
+                actual: sampler.glsl_name(),
+            });
+        }
+        let unit = self.texture_unit(declared.name)?;
+        texture.bind_to_unit(unit);
+        if let Some(location) = &declared.location {
+            self.upload_uniform(location, UniformValue::Int(unit as i32));
+        }
+
+        Ok(())
+    }
+
+    /// Bind a [`Sampler`] to the texture unit of a sampler uniform, assuming the shader is bound
+    /// by [`ShaderProgram::bind`]
+    ///
+    /// The sampler's options replace those of the texture bound with
+    /// [`ShaderProgram::bind_texture`] for the next draw, unless `None` is passed to unbind it.
+    /// Like texture units, samplers are unbound after every draw, so they don't affect textures
+    /// that later draws put on the same unit. The uniform must be declared in the
+    /// [`ShaderDescription`] as a sampler type; the errors are the same as
+    /// [`ShaderProgram::bind_texture`].
+    pub fn bind_sampler(&self, name: &str, sampler: Option<&Sampler>) -> Result<(), GolemError> {
+        if !self.is_bound() {
+            return Err(GolemError::NotCurrentProgram);
+        }
+        let declared = self
+            .uniforms
+            .iter()
+            .find(|u| u.name == name)
+            .ok_or_else(|| GolemError::NoSuchUniform(name.to_owned()))?;
+        if !declared.u_type.is_sampler() {
+            return Err(GolemError::UniformTypeMismatch {
+                name: name.to_owned(),
+                expected: declared.u_type.glsl_name(),
+                actual: "sampler".to_owned(),
+            });
+        }
+        let unit = self.texture_unit(declared.name)?;
+        let mut sampler_units = self.sampler_units.borrow_mut();
+        match sampler {
+            Some(sampler) => {
+                sampler.bind_to_unit(unit);
+                if !sampler_units.contains(&unit) {
+                    sampler_units.push(unit);
+                }
+            }
+            None => {
+                unsafe { self.ctx.0.gl.bind_sampler(unit, None) };
+                sampler_units.retain(|bound| *bound != unit);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the texture unit for a sampler, allocating one if it doesn't have one for this draw yet
+    fn texture_unit(&self, name: &'static str) -> Result<u32, GolemError> {
+        let mut units = self.texture_units.borrow_mut();
+        // Unit 0 is reserved by golem, so units start at 1
+        if let Some(index) = units.iter().position(|sampler| *sampler == name) {
+            return Ok(index as u32 + 1);
+        }
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glActiveTexture.xhtml
+        // The unit must be less than MAX_COMBINED_TEXTURE_IMAGE_UNITS
+        let max_units = self.ctx.max_texture_units();
+        if units.len() + 1 >= max_units as usize {
+            return Err(GolemError::TooManyTextures(max_units.saturating_sub(1)));
+        }
+        units.push(name);
+
+        Ok(units.len() as u32)
+    }
+
+    /// Hand out the texture units again for the next draw, unbinding any samplers on them
+    fn reset_texture_units(&self) {
+        self.texture_units.borrow_mut().clear();
+        for unit in self.sampler_units.borrow_mut().drain(..) {
+            unsafe { self.ctx.0.gl.bind_sampler(unit, None) };
+        }
+    }
+
+    fn upload_uniform(&self, location: &GlUniformLocation, uniform: UniformValue) {
+        use UniformValue::*;
+        let gl = &self.ctx.0.gl;
+        let location = Some(location);
+        unsafe {
+            match uniform {
+                Int(x) => gl.uniform_1_i32(location, x),
+                IVector2([x, y]) => gl.uniform_2_i32(location, x, y),
+                IVector3([x, y, z]) => gl.uniform_3_i32(location, x, y, z),
+                IVector4([x, y, z, w]) => gl.uniform_4_i32(location, x, y, z, w),
+                Float(x) => gl.uniform_1_f32(location, x),
+                Vector2([x, y]) => gl.uniform_2_f32(location, x, y),
+                Vector3([x, y, z]) => gl.uniform_3_f32(location, x, y, z),
+                Vector4([x, y, z, w]) => gl.uniform_4_f32(location, x, y, z, w),
+                Matrix2(mat) => gl.uniform_matrix_2_f32_slice(location, false, &mat),
+                Matrix3(mat) => gl.uniform_matrix_3_f32_slice(location, false, &mat),
+                Matrix4(mat) => gl.uniform_matrix_4_f32_slice(location, false, &mat),
+                IntArray(x) => gl.uniform_1_i32_slice(location, x),
+                IVector2Array(x) => gl.uniform_2_i32_slice(location, bytemuck::cast_slice(x)),
+                IVector3Array(x) => gl.uniform_3_i32_slice(location, bytemuck::cast_slice(x)),
+                IVector4Array(x) => gl.uniform_4_i32_slice(location, bytemuck::cast_slice(x)),
+                FloatArray(x) => gl.uniform_1_f32_slice(location, x),
+                Vector2Array(x) => gl.uniform_2_f32_slice(location, bytemuck::cast_slice(x)),
+                Vector3Array(x) => gl.uniform_3_f32_slice(location, bytemuck::cast_slice(x)),
+                Vector4Array(x) => gl.uniform_4_f32_slice(location, bytemuck::cast_slice(x)),
+                Matrix2Array(mat) => {
+                    gl.uniform_matrix_2_f32_slice(location, false, bytemuck::cast_slice(mat))
+                }
+                Matrix3Array(mat) => {
+                    gl.uniform_matrix_3_f32_slice(location, false, bytemuck::cast_slice(mat))
+                }
+                Matrix4Array(mat) => {
+                    gl.uniform_matrix_4_f32_slice(location, false, bytemuck::cast_slice(mat))
+                }
+            }
+        }
+    }
+
     /// Bind this shader to use it, either to [`set a uniform`] or to [`draw`]
     ///
     /// [`set a uniform`]: ShaderProgram::set_uniform
@@ -256,10 +609,10 @@ impl ShaderProgram {
     ///    elements are valid and in-bounds.
     ///
     /// [`Surface::bind`]: crate::Surface::bind
-    pub unsafe fn draw(
+    pub unsafe fn draw<V: Vertex, E: ElementIndex>(
         &self,
-        vb: &VertexBuffer,
-        eb: &ElementBuffer,
+        vb: &Buffer<V>,
+        eb: &Buffer<E>,
         range: Range<usize>,
         geometry: GeometryMode,
     ) -> Result<(), GolemError> {
@@ -273,6 +626,50 @@ impl ShaderProgram {
         Ok(())
     }
 
+    /// Draw the given range of vertices from the vertex buffer with this shader, without an
+    /// [`ElementBuffer`]
+    ///
+    /// Each vertex is used exactly once, in order, so the range indicates which vertices to draw.
+    /// The GeometryMode determines what the vertices produce, like in [`ShaderProgram::draw`].
+    ///
+    /// The range must fall within the vertices of the buffer, as determined by the length of the
+    /// data last uploaded to the [`VertexBuffer`] and the stride of the [`vertex_input`] (which
+    /// is checked for via an `assert!`.)
+    ///
+    /// The `ShaderProgram` must be bound first, see [`ShaderProgram::bind`].
+    ///
+    /// [`vertex_input`]: ShaderDescription::vertex_input
+    pub fn draw_arrays<V: Vertex>(
+        &self,
+        vb: &Buffer<V>,
+        range: Range<usize>,
+        geometry: GeometryMode,
+    ) -> Result<(), GolemError> {
+        if !self.is_bound() {
+            return Err(GolemError::NotCurrentProgram);
+        }
+        let stride = attribute_stride(&self.input) as usize;
+        assert!(
+            range.end * stride <= vb.data_size(),
+            "The range exceeded the size of the vertex buffer"
+        );
+        log::trace!("Binding the attributes to draw");
+        self.bind_attributes(vb, &self.input, 0, 0);
+        self.disable_dangling_attributes(self.input.len() as u32);
+        log::trace!("Dispatching draw command");
+        let length = range.end - range.start;
+        unsafe {
+            self.ctx.0.gl.draw_arrays(
+                ShaderProgram::shape_type(geometry),
+                range.start as i32,
+                length as i32,
+            );
+        }
+        self.reset_texture_units();
+
+        Ok(())
+    }
+
     /// Set up a [`VertexBuffer`] and [`ElementBuffer`] to draw multiple times with the same
     /// buffers.
     ///
@@ -280,43 +677,118 @@ impl ShaderProgram {
     ///
     /// See [`ShaderProgram::draw_prepared`] to execute the draw calls. If you're only drawing the
     /// buffers once before replacing their data, see [`ShaderProgram::draw`].
-    pub fn prepare_draw(&self, vb: &VertexBuffer, eb: &ElementBuffer) -> Result<(), GolemError> {
+    pub fn prepare_draw<V: Vertex, E: ElementIndex>(
+        &self,
+        vb: &Buffer<V>,
+        eb: &Buffer<E>,
+    ) -> Result<(), GolemError> {
         if !self.is_bound() {
             Err(GolemError::NotCurrentProgram)
         } else {
-            eb.bind();
-            vb.bind();
-            let stride: i32 = self.input.iter().map(|attr| attr.size()).sum();
-            let stride = stride * size_of::<f32>() as i32;
-            let mut offset = 0;
+            self.bind_elements(eb);
             log::trace!("Binding the attributes to draw");
-            let gl = &self.ctx.0.gl;
-            for (index, attr) in self.input.iter().enumerate() {
-                let size = attr.size();
-                unsafe {
-                    let pos_attrib = index as u32;
-                    gl.enable_vertex_attrib_array(pos_attrib);
+            self.bind_attributes(vb, &self.input, 0, 0);
+            self.disable_dangling_attributes(self.input.len() as u32);
+
+            Ok(())
+        }
+    }
+
+    /// Set up a [`VertexBuffer`], a per-instance [`VertexBuffer`] and an [`ElementBuffer`] to
+    /// draw instances multiple times with the same buffers.
+    ///
+    /// The `ShaderProgram` must be bound first, see [`ShaderProgram::bind`]. Per-instance attributes
+    /// require OpenGL 3.3 or the `ARB_instanced_arrays` extension on desktop, and this returns
+    /// [`GolemError::ExtensionUnavailable`] without them.
+    ///
+    /// See [`ShaderProgram::draw_prepared_instanced`] to execute the draw calls. If you're only
+    /// drawing the buffers once before replacing their data, see
+    /// [`ShaderProgram::draw_instanced`].
+    pub fn prepare_draw_instanced<V: Vertex, I: Vertex, E: ElementIndex>(
+        &self,
+        vb: &Buffer<V>,
+        instances: &Buffer<I>,
+        eb: &Buffer<E>,
+    ) -> Result<(), GolemError> {
+        if !self.is_bound() {
+            Err(GolemError::NotCurrentProgram)
+        } else if !self.ctx.has_feature(3, 3, "ARB_instanced_arrays") {
+            Err(GolemError::ExtensionUnavailable("ARB_instanced_arrays"))
+        } else {
+            self.bind_elements(eb);
+            log::trace!("Binding the attributes to draw instanced");
+            self.bind_attributes(vb, &self.input, 0, 0);
+            self.bind_attributes(instances, &self.instance_input, self.input.len() as u32, 1);
+            self.disable_dangling_attributes((self.input.len() + self.instance_input.len()) as u32);
+
+            Ok(())
+        }
+    }
+
+    fn bind_elements<E: ElementIndex>(&self, eb: &Buffer<E>) {
+        eb.bind();
+        // Remember the index type, so draw_prepared can pass it to the draw call
+        *self.element_type.borrow_mut() = (E::GL_TYPE, size_of::<E>());
+    }
+
+    fn bind_attributes<V: Vertex>(
+        &self,
+        buffer: &Buffer<V>,
+        attributes: &[Attribute],
+        first_index: u32,
+        divisor: u32,
+    ) {
+        buffer.bind();
+        let stride = attribute_stride(attributes);
+        assert_eq!(
+            stride as usize % size_of::<V>(),
+            0,
+            "The size of the vertex type doesn't match the shader's attributes"
+        );
+        let mut offset = 0;
+        let gl = &self.ctx.0.gl;
+        for (index, attr) in attributes.iter().enumerate() {
+            let size = attr.size();
+            let component = attr.component_type();
+            offset = align_to(offset, attr.alignment());
+            let pos_attrib = first_index + index as u32;
+            unsafe {
+                gl.enable_vertex_attrib_array(pos_attrib);
+                // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glVertexAttribPointer.xhtml
+                // Integer components must use the I variant to avoid being converted to floats
+                if component.is_integer() {
+                    gl.vertex_attrib_pointer_i32(
+                        pos_attrib,
+                        size,
+                        component.to_gl(),
+                        stride,
+                        offset,
+                    );
+                } else {
                     gl.vertex_attrib_pointer_f32(
                         pos_attrib,
                         size,
-                        glow::FLOAT,
-                        false,
+                        component.to_gl(),
+                        component.is_normalized(),
                         stride,
                         offset,
                     );
                 }
-                offset += size * size_of::<f32>() as i32;
-            }
-            // Disable any dangling vertex attributes
-            let current_max_attrib = self.input.len() as u32;
-            let previous_max_attrib = self.ctx.max_attrib(current_max_attrib);
-            for i in current_max_attrib..previous_max_attrib {
-                unsafe {
-                    gl.disable_vertex_attrib_array(i);
-                }
             }
+            // The divisor must be reset for regular vertex attributes, in case a previous
+            // instanced draw used the same index
+            self.ctx.set_attribute_divisor(pos_attrib, divisor);
+            offset += attr.byte_size();
+        }
+    }
 
-            Ok(())
+    fn disable_dangling_attributes(&self, current_max_attrib: u32) {
+        let gl = &self.ctx.0.gl;
+        let previous_max_attrib = self.ctx.max_attrib(current_max_attrib);
+        for i in current_max_attrib..previous_max_attrib {
+            unsafe {
+                gl.disable_vertex_attrib_array(i);
+            }
         }
     }
 
@@ -349,12 +821,90 @@ impl ShaderProgram {
     pub unsafe fn draw_prepared(&self, range: Range<usize>, geometry: GeometryMode) {
         log::trace!("Dispatching draw command");
         let length = range.end - range.start;
+        let (element_type, element_size) = *self.element_type.borrow();
         self.ctx.0.gl.draw_elements(
             ShaderProgram::shape_type(geometry),
             length as i32,
-            glow::UNSIGNED_INT,
-            (range.start * size_of::<u32>()) as i32,
+            element_type,
+            (range.start * element_size) as i32,
+        );
+        self.reset_texture_units();
+    }
+
+    /// Draw `instances` copies of the given elements from the element buffer with this shader
+    ///
+    /// The vertex inputs are read from `vb` and the [`instance_input`] from `instance_buffer`,
+    /// advancing once per instance instead of once per vertex. Otherwise this behaves like
+    /// [`ShaderProgram::draw`].
+    ///
+    /// The data last uploaded to the `instance_buffer` must hold at least `instances` sets of the
+    /// [`instance_input`] (which is checked for via an `assert!`.)
+    ///
+    /// The `ShaderProgram` must be bound first, see [`ShaderProgram::bind`]. The extension
+    /// requirements are the same as [`ShaderProgram::prepare_draw_instanced`].
+    ///
+    /// # Safety
+    ///
+    /// The elements in the [`ElementBuffer`] are not checked against the size of the
+    /// [`VertexBuffer`]. See [`ShaderProgram::draw`] for details.
+    ///
+    /// [`instance_input`]: ShaderDescription::instance_input
+    pub unsafe fn draw_instanced<V: Vertex, I: Vertex, E: ElementIndex>(
+        &self,
+        vb: &Buffer<V>,
+        instance_buffer: &Buffer<I>,
+        eb: &Buffer<E>,
+        range: Range<usize>,
+        instances: usize,
+        geometry: GeometryMode,
+    ) -> Result<(), GolemError> {
+        assert!(
+            range.end <= eb.size(),
+            "The range exceeded the size of the element buffer"
+        );
+        let instance_stride = attribute_stride(&self.instance_input) as usize;
+        assert!(
+            instances * instance_stride <= instance_buffer.data_size(),
+            "The instances exceeded the size of the instance buffer"
+        );
+        // prepare_draw_instanced also takes care of ensuring this program is current
+        self.prepare_draw_instanced(vb, instance_buffer, eb)?;
+        self.draw_prepared_instanced(range, instances, geometry);
+        Ok(())
+    }
+
+    /// Draw `instances` copies of the given elements from the prepared buffers with this shader
+    ///
+    /// This relies on the caller having a valid prepared state: see
+    /// [`prepare_draw_instanced`].
+    ///
+    /// # Safety
+    ///
+    /// The same safety concerns as [`draw_prepared`] apply, with [`prepare_draw_instanced`] in
+    /// place of [`prepare_draw`]. In addition, the prepared instance buffer must hold at least
+    /// `instances` sets of the per-instance attributes. See [`draw_instanced`] for details.
+    ///
+    /// [`prepare_draw`]: ShaderProgram::prepare_draw
+    /// [`prepare_draw_instanced`]: ShaderProgram::prepare_draw_instanced
+    /// [`draw_prepared`]: ShaderProgram::draw_prepared
+    /// [`draw_instanced`]: ShaderProgram::draw_instanced
+    pub unsafe fn draw_prepared_instanced(
+        &self,
+        range: Range<usize>,
+        instances: usize,
+        geometry: GeometryMode,
+    ) {
+        log::trace!("Dispatching instanced draw command");
+        let length = range.end - range.start;
+        let (element_type, element_size) = *self.element_type.borrow();
+        self.ctx.0.gl.draw_elements_instanced(
+            ShaderProgram::shape_type(geometry),
+            length as i32,
+            element_type,
+            (range.start * element_size) as i32,
+            instances as i32,
         );
+        self.reset_texture_units();
     }
 
     fn shape_type(geometry: GeometryMode) -> u32 {
@@ -381,3 +931,59 @@ impl Drop for ShaderProgram {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dimension::*;
+
+    #[test]
+    fn attributes_are_laid_out_like_repr_c() {
+        let u8x3 = AttributeType::VectorOf(ComponentType::U8 { normalized: true }, D3);
+        let attributes = [
+            Attribute::new("color", u8x3),
+            Attribute::new("position", AttributeType::Vector(D2)),
+        ];
+        // 3 bytes of color, padded to the 4-byte alignment of the floats
+        assert_eq!(attribute_stride(&attributes), 12);
+
+        let attributes = [
+            Attribute::new("position", AttributeType::Vector(D3)),
+            Attribute::new(
+                "id",
+                AttributeType::ScalarOf(ComponentType::U16 { normalized: false }),
+            ),
+        ];
+        // The struct is padded at the end to a multiple of its alignment
+        assert_eq!(attribute_stride(&attributes), 16);
+        assert_eq!(attribute_stride(&[]), 0);
+    }
+
+    #[test]
+    fn fragment_outputs_are_declared_in_order() {
+        let outputs = [
+            Attribute::new("color", AttributeType::Vector(D4)),
+            Attribute::new("normal", AttributeType::Vector(D4)),
+        ];
+        let inputs = [Attribute::new(
+            "id",
+            AttributeType::ScalarOf(ComponentType::U32),
+        )];
+        let text = generate_shader_text(false, "void main() {}", &inputs, &outputs, &[], &[]);
+        let color = text
+            .find("out vec4 color;")
+            .expect("The first output is declared");
+        let normal = text
+            .find("out vec4 normal;")
+            .expect("The second output is declared");
+        assert!(color < normal);
+        // Integers can't be interpolated, so they must be flat
+        assert!(text.contains("flat in uint id;"));
+        if cfg!(target_arch = "wasm32") {
+            assert!(text.starts_with("#version 300 es"));
+            assert!(text.contains("layout(location = 1) out vec4 normal;"));
+        } else {
+            assert!(text.starts_with("#version 150"));
+        }
+    }
+}
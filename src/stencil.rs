@@ -0,0 +1,167 @@
+//! Various options to specify how the stencil buffer is tested and updated
+//!
+//! The stencil buffer stores an integer per pixel, which can be compared against a reference
+//! value to discard pixels (masking, portals, outlines) and updated as geometry is drawn.
+
+/// The state of stencil test settings
+///
+/// Each incoming pixel's stencil value is compared to `reference` using `function`, with both
+/// values first masked by `test_mask`. Depending on whether the stencil test and then the depth
+/// test pass, the stored stencil value is updated by the operations in `front` or `back`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StencilTestMode {
+    /// How to compare the reference value to the value present in the stencil buffer
+    ///
+    /// Default is `StencilFunction::Always`.
+    pub function: StencilFunction,
+    /// The value the stencil buffer is compared to, and written by [`StencilOperation::Replace`]
+    ///
+    /// Default is `0`.
+    pub reference: i32,
+    /// A mask that is ANDed with both the reference and the stored value before comparing them
+    ///
+    /// Default is `0xFFFFFFFF`, i.e. "all bits are compared".
+    pub test_mask: u32,
+    /// A mask that controls which bits of the stencil buffer can be written
+    ///
+    /// Default is `0xFFFFFFFF`, i.e. "all bits are written". This also applies when the stencil
+    /// buffer is cleared by [`Context::clear`].
+    ///
+    /// [`Context::clear`]: crate::Context::clear
+    pub write_mask: u32,
+    /// How to update the stencil buffer for front-facing polygons
+    ///
+    /// Points, lines, and all other non-polygon geometry are considered front-facing.
+    pub front: StencilOperations,
+    /// How to update the stencil buffer for back-facing polygons
+    pub back: StencilOperations,
+}
+
+impl Default for StencilTestMode {
+    fn default() -> Self {
+        Self {
+            function: StencilFunction::default(),
+            reference: 0,
+            test_mask: !0,
+            write_mask: !0,
+            front: StencilOperations::default(),
+            back: StencilOperations::default(),
+        }
+    }
+}
+
+/// The operations applied to the stencil buffer, depending on the outcome of the tests
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct StencilOperations {
+    /// Applied when the stencil test fails
+    ///
+    /// Default is `StencilOperation::Keep`.
+    pub stencil_fail: StencilOperation,
+    /// Applied when the stencil test passes, but the depth test fails
+    ///
+    /// Default is `StencilOperation::Keep`.
+    pub depth_fail: StencilOperation,
+    /// Applied when both the stencil test and the depth test pass
+    ///
+    /// If depth testing is disabled, the depth test is considered to always pass. Default is
+    /// `StencilOperation::Keep`.
+    pub pass: StencilOperation,
+}
+
+/// Function used to compare the reference value with the value present in the stencil buffer
+///
+/// Unlike depth testing, the reference value is on the left side of the comparison:
+/// `StencilFunction::Less` passes if `reference & test_mask < stored & test_mask`.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StencilFunction {
+    /// Pixel is never drawn
+    Never,
+    /// Pixel is drawn if the reference value is less than the stored one
+    Less,
+    /// Pixel is drawn if the reference value is equal to the stored one
+    Equal,
+    /// Pixel is drawn if the reference value is less than or equal to the stored one
+    LessOrEqual,
+    /// Pixel is drawn if the reference value is greater than the stored one
+    Greater,
+    /// Pixel is drawn if the reference value is not equal to the stored one
+    NotEqual,
+    /// Pixel is drawn if the reference value is greater than or equal to the stored one
+    GreaterOrEqual,
+    /// Pixel is always drawn
+    #[default]
+    Always,
+}
+
+impl StencilFunction {
+    pub(crate) fn to_gl(self) -> u32 {
+        use StencilFunction::*;
+        match self {
+            Never => glow::NEVER,
+            Less => glow::LESS,
+            Equal => glow::EQUAL,
+            LessOrEqual => glow::LEQUAL,
+            Greater => glow::GREATER,
+            NotEqual => glow::NOTEQUAL,
+            GreaterOrEqual => glow::GEQUAL,
+            Always => glow::ALWAYS,
+        }
+    }
+}
+
+/// How to change the value stored in the stencil buffer
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StencilOperation {
+    /// Keep the stored value
+    #[default]
+    Keep,
+    /// Set the stored value to 0
+    Zero,
+    /// Set the stored value to the [`StencilTestMode::reference`] value
+    Replace,
+    /// Increase the stored value by 1, stopping at the maximum value
+    Increment,
+    /// Increase the stored value by 1, wrapping around to 0 past the maximum value
+    IncrementWrap,
+    /// Decrease the stored value by 1, stopping at 0
+    Decrement,
+    /// Decrease the stored value by 1, wrapping around to the maximum value past 0
+    DecrementWrap,
+    /// Flip the bits of the stored value
+    Invert,
+}
+
+impl StencilOperation {
+    pub(crate) fn to_gl(self) -> u32 {
+        use StencilOperation::*;
+        match self {
+            Keep => glow::KEEP,
+            Zero => glow::ZERO,
+            Replace => glow::REPLACE,
+            Increment => glow::INCR,
+            IncrementWrap => glow::INCR_WRAP,
+            Decrement => glow::DECR,
+            DecrementWrap => glow::DECR_WRAP,
+            Invert => glow::INVERT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_mode_matches_initial_gl_state() {
+        let mode = StencilTestMode::default();
+        assert_eq!(mode.function.to_gl(), glow::ALWAYS);
+        assert_eq!(mode.reference, 0);
+        assert_eq!(mode.test_mask, u32::MAX);
+        assert_eq!(mode.write_mask, u32::MAX);
+        for ops in [mode.front, mode.back].iter() {
+            assert_eq!(ops.stencil_fail.to_gl(), glow::KEEP);
+            assert_eq!(ops.depth_fail.to_gl(), glow::KEEP);
+            assert_eq!(ops.pass.to_gl(), glow::KEEP);
+        }
+    }
+}
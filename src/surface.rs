@@ -1,35 +1,224 @@
+use crate::blit::{BlitFilter, BlitMask, BlitRegion};
+use crate::depth::DepthFormat;
 use crate::*;
+use std::vec::Vec;
 
 /// A framebuffer that allows render-to-texture
+///
+/// Cube map, array, and 3D textures can be attached too, rendering to one face or layer at a
+/// time (see [`Surface::set_cube_face`] and [`Surface::set_layer`]).
+///
+/// A Surface can also render to several color textures at once, see [`Surface::with_textures`]
+/// and [`ShaderDescription::fragment_output`]. To use depth (or stencil) testing while rendering
+/// to one, give it a depth buffer with [`Surface::set_depth_buffer`] or
+/// [`Surface::put_depth_texture`].
 pub struct Surface {
     pub(crate) ctx: Context,
     pub(crate) id: GlFramebuffer,
-    pub(crate) texture: Option<Texture>,
+    /// The textures attached to each color attachment, starting with `COLOR_ATTACHMENT0`
+    pub(crate) textures: Vec<Option<Texture>>,
+    /// The layer of each layered texture that is rendered to, or the face of each cube map
+    layers: Vec<u32>,
+    depth: Option<DepthAttachment>,
+}
+
+/// The depth buffer of a [`Surface`]
+enum DepthAttachment {
+    Renderbuffer(GlRenderbuffer, DepthFormat),
+    Texture(Texture),
 }
 
 impl Surface {
     /// Create a new Surface to render to, backed by the given texture
-    pub fn new(ctx: &Context, texture: Texture) -> Result<Surface, GolemError> {
+    ///
+    /// If the texture can't be rendered to, this returns an [`AttachmentError`] holding the
+    /// texture.
+    pub fn new(ctx: &Context, texture: Texture) -> Result<Surface, AttachmentError> {
+        Surface::with_textures(ctx, vec![texture])
+    }
+
+    /// Create a new Surface that renders to several textures at once
+    ///
+    /// The first texture is attached to `COLOR_ATTACHMENT0`, the second to `COLOR_ATTACHMENT1`,
+    /// and so on. Each fragment output of a [`ShaderDescription::fragment_output`] writes to the
+    /// texture at the same index. At least one texture is required, and there can be at most as
+    /// many as the GPU's limit on color attachments (both checked for via an `assert!`.)
+    ///
+    /// If the textures can't be rendered to, this returns an [`AttachmentError`] holding all of
+    /// them, in the order they were given.
+    pub fn with_textures(
+        ctx: &Context,
+        textures: Vec<Texture>,
+    ) -> Result<Surface, AttachmentError> {
+        assert!(!textures.is_empty(), "A surface needs at least one texture");
+        assert!(
+            textures.len() as i32 <= max_color_attachments(ctx),
+            "There were more textures than the maximum of {} color attachments",
+            max_color_attachments(ctx)
+        );
         let ctx = Context(ctx.0.clone());
-        let gl = &ctx.0.gl;
-        let id = unsafe { gl.create_framebuffer() }?;
+        let id = match unsafe { ctx.0.gl.create_framebuffer() } {
+            Ok(id) => id,
+            Err(error) => return Err(AttachmentError::new(error.into(), textures)),
+        };
+        let mut surface = Surface {
+            ctx,
+            id,
+            textures: Vec::new(),
+            layers: Vec::new(),
+            depth: None,
+        };
+        for (index, texture) in textures.into_iter().enumerate() {
+            surface.replace_texture_at(index as u32, Some(texture));
+        }
+        if let Err(error) = surface.check_complete() {
+            let textures = surface.textures.drain(..).flatten().collect();
+            return Err(AttachmentError::new(error, textures));
+        }
+
+        Ok(surface)
+    }
+
+    /// Give the Surface a depth buffer with the given format, sized to match its texture
+    ///
+    /// The depth buffer is only used for rendering, and can't be read in a shader: to do that,
+    /// see [`Surface::put_depth_texture`]. Passing `None` removes the current depth buffer or
+    /// depth texture (dropping the texture).
+    ///
+    /// If the texture is later resized or replaced, call this again to resize the depth buffer.
+    /// The Surface must have a texture (which is checked for via an `assert!`). If the Surface
+    /// can't be rendered to with the new depth buffer, this returns
+    /// [`GolemError::IncompleteFramebuffer`].
+    pub fn set_depth_buffer(&mut self, format: Option<DepthFormat>) -> Result<(), GolemError> {
+        self.remove_depth();
+        let format = match format {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+        let texture = self.textures[0]
+            .as_ref()
+            .expect("The surface had no attached image when set_depth_buffer was called");
+        let gl = &self.ctx.0.gl;
         unsafe {
-            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(id));
+            let renderbuffer = gl.create_renderbuffer()?;
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+            gl.renderbuffer_storage(
+                glow::RENDERBUFFER,
+                format.gl_internal_format(),
+                texture.width() as i32,
+                texture.height() as i32,
+            );
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                format.gl_attachment(),
+                glow::RENDERBUFFER,
+                Some(renderbuffer),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
+            self.depth = Some(DepthAttachment::Renderbuffer(renderbuffer, format));
+        }
+
+        self.check_complete()
+    }
+
+    /// Use a depth texture as the Surface's depth buffer, replacing any current one
+    ///
+    /// The texture must have been created with [`Texture::set_depth_image`], and should be the
+    /// same size as the Surface's color texture (both are checked for via an `assert!`.) If the
+    /// Surface can't be rendered to with the new depth texture, this returns
+    /// [`GolemError::IncompleteFramebuffer`], leaving the texture attached.
+    pub fn put_depth_texture(&mut self, texture: Texture) -> Result<(), GolemError> {
+        let format = texture
+            .depth_format()
+            .expect("The texture passed to put_depth_texture wasn't a depth texture");
+        if let Some(color) = &self.textures[0] {
+            assert!(
+                color.width() == texture.width() && color.height() == texture.height(),
+                "The depth texture was a different size than the surface's texture"
+            );
+        }
+        self.remove_depth();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
             gl.framebuffer_texture_2d(
                 glow::FRAMEBUFFER,
-                glow::COLOR_ATTACHMENT0,
+                format.gl_attachment(),
                 glow::TEXTURE_2D,
                 Some(texture.id),
                 0,
             );
-            gl.bind_framebuffer(glow::FRAMEBUFFER, *ctx.0.current_surface.borrow());
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
         }
+        self.depth = Some(DepthAttachment::Texture(texture));
 
-        Ok(Surface {
-            ctx,
-            id,
-            texture: Some(texture),
-        })
+        self.check_complete()
+    }
+
+    /// Remove the depth texture from the Surface, to operate on it
+    ///
+    /// If the Surface has a depth buffer from [`Surface::set_depth_buffer`] instead, it is left in
+    /// place and this returns `None`.
+    pub fn take_depth_texture(&mut self) -> Option<Texture> {
+        match self.depth {
+            Some(DepthAttachment::Texture(_)) => match self.remove_depth() {
+                Some(DepthAttachment::Texture(texture)) => Some(texture),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Borrow the depth texture the Surface is holding
+    ///
+    /// # Safety
+    ///
+    /// The same 'texture loop' concerns as [`Surface::borrow_texture`] apply: don't read the
+    /// depth texture in a shader while rendering to this Surface.
+    pub unsafe fn borrow_depth_texture(&self) -> Option<&Texture> {
+        match &self.depth {
+            Some(DepthAttachment::Texture(texture)) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// The format of the Surface's depth buffer or depth texture, if it has one
+    pub fn depth_format(&self) -> Option<DepthFormat> {
+        match &self.depth {
+            Some(DepthAttachment::Renderbuffer(_, format)) => Some(*format),
+            Some(DepthAttachment::Texture(texture)) => texture.depth_format(),
+            None => None,
+        }
+    }
+
+    /// Detach the depth buffer, deleting it if it's a renderbuffer
+    fn remove_depth(&mut self) -> Option<DepthAttachment> {
+        let depth = self.depth.take()?;
+        let format = match &depth {
+            DepthAttachment::Renderbuffer(_, format) => *format,
+            DepthAttachment::Texture(texture) => texture
+                .depth_format()
+                .expect("Depth textures always have a depth format"),
+        };
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                format.gl_attachment(),
+                glow::RENDERBUFFER,
+                None,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
+            if let DepthAttachment::Renderbuffer(renderbuffer, _) = depth {
+                gl.delete_renderbuffer(renderbuffer);
+                return None;
+            }
+        }
+
+        Some(depth)
     }
 
     /// Check if a texture is attached to this Surface
@@ -37,7 +226,7 @@ impl Surface {
     /// Textures can be attached via [`Surface::put_texture`] and removed via
     /// [`Surface::take_texture`].
     pub fn has_texture(&self) -> bool {
-        self.texture.is_some()
+        self.textures[0].is_some()
     }
 
     /// Check if this surface is bound to be operated on
@@ -56,32 +245,18 @@ impl Surface {
     /// Until another texture is added via [`Surface::put_texture`], operations on the Surface will
     /// panic.
     pub fn take_texture(&mut self) -> Option<Texture> {
-        let gl = &self.ctx.0.gl;
-        unsafe {
-            gl.framebuffer_texture_2d(
-                glow::FRAMEBUFFER,
-                glow::COLOR_ATTACHMENT0,
-                glow::TEXTURE_2D,
-                None,
-                0,
-            );
-        }
-        self.texture.take()
+        self.take_texture_at(0)
     }
 
     /// Put a texture into the Surface to operate on
-    pub fn put_texture(&mut self, texture: Texture) {
-        let gl = &self.ctx.0.gl;
-        unsafe {
-            gl.framebuffer_texture_2d(
-                glow::FRAMEBUFFER,
-                glow::COLOR_ATTACHMENT0,
-                glow::TEXTURE_2D,
-                Some(texture.id),
-                0,
-            );
-        }
-        self.texture = Some(texture);
+    ///
+    /// If the Surface can't be rendered to with the new texture (for example if the texture has
+    /// no image, or a format that can't be rendered to), this returns an [`AttachmentError`]
+    /// holding the texture, and the Surface keeps its previous texture.
+    pub fn put_texture(&mut self, texture: Texture) -> Result<(), AttachmentError> {
+        self.put_texture_at(0, texture)?;
+
+        Ok(())
     }
 
     /// Borrow the texture the Surface is holding
@@ -93,7 +268,207 @@ impl Surface {
     /// the texture cannot be used in the rendering pipeline. It is important to only ever render
     /// to the Surface *or* use its texture, not both.
     pub unsafe fn borrow_texture(&self) -> Option<&Texture> {
-        self.texture.as_ref()
+        self.borrow_texture_at(0)
+    }
+
+    /// The number of color attachments, including any that currently have no texture
+    pub fn texture_count(&self) -> u32 {
+        self.textures.len() as u32
+    }
+
+    /// Remove the texture from the given color attachment, to operate on it
+    ///
+    /// Removing the texture at index 0 has the same restrictions as [`Surface::take_texture`].
+    pub fn take_texture_at(&mut self, index: u32) -> Option<Texture> {
+        let texture = self.textures.get_mut(index as usize)?.take()?;
+        self.attach_color(index);
+
+        Some(texture)
+    }
+
+    /// Put a texture into the given color attachment, replacing (and returning) any texture there
+    ///
+    /// Cube maps are attached by their [`CubeFace::PositiveX`] face, and array and 3D textures by
+    /// their first layer; see [`Surface::set_cube_face`] and [`Surface::set_layer`] to choose
+    /// another.
+    ///
+    /// The index must be below both [`glow::MAX_COLOR_ATTACHMENTS`] and [`glow::MAX_DRAW_BUFFERS`]
+    /// (which is checked for via an `assert!`.) Like [`Surface::put_texture`], if the Surface
+    /// can't be rendered to afterwards, this puts the previous texture back and returns an
+    /// [`AttachmentError`] holding the new one.
+    pub fn put_texture_at(
+        &mut self,
+        index: u32,
+        texture: Texture,
+    ) -> Result<Option<Texture>, AttachmentError> {
+        let max_attachments = max_color_attachments(&self.ctx);
+        assert!(
+            (index as i32) < max_attachments,
+            "The color attachment index was above the maximum of {}",
+            max_attachments - 1
+        );
+        let count = self.textures.len();
+        let (previous, previous_layer) = self.replace_texture_at(index, Some(texture));
+        if let Err(error) = self.check_complete() {
+            let (texture, _) = self.replace_texture_at(index, previous);
+            self.layers[index as usize] = previous_layer;
+            self.textures.truncate(count);
+            self.layers.truncate(count);
+            self.attach_color(index.min(count as u32 - 1));
+            let texture = texture.expect("The texture was just attached");
+            return Err(AttachmentError::new(error, vec![texture]));
+        }
+
+        Ok(previous)
+    }
+
+    /// Attach a texture (or nothing) at the index, returning the previous texture and its layer
+    fn replace_texture_at(
+        &mut self,
+        index: u32,
+        texture: Option<Texture>,
+    ) -> (Option<Texture>, u32) {
+        if self.textures.len() <= index as usize {
+            self.textures.resize_with(index as usize + 1, || None);
+            self.layers.resize(index as usize + 1, 0);
+        }
+        let previous = std::mem::replace(&mut self.textures[index as usize], texture);
+        let previous_layer = std::mem::replace(&mut self.layers[index as usize], 0);
+        self.attach_color(index);
+
+        (previous, previous_layer)
+    }
+
+    /// Choose which face of the cube map at the given color attachment is rendered to
+    ///
+    /// The attachment must hold a cube map texture (which is checked for via an `assert!`.) If
+    /// the Surface can't be rendered to afterwards, this returns
+    /// [`GolemError::IncompleteFramebuffer`].
+    pub fn set_cube_face(&mut self, index: u32, face: CubeFace) -> Result<(), GolemError> {
+        let kind = self.attached_kind(index);
+        assert!(
+            kind == Some(TextureKind::CubeMap),
+            "The color attachment didn't hold a cube map"
+        );
+        self.layers[index as usize] = face as u32;
+        self.attach_color(index);
+
+        self.check_complete()
+    }
+
+    /// Choose which layer of the array or 3D texture at the given color attachment is rendered to
+    ///
+    /// The attachment must hold an array or 3D texture, and the layer must be less than its
+    /// [`Texture::layers`] (both checked for via an `assert!`.) If the Surface can't be rendered
+    /// to afterwards, this returns [`GolemError::IncompleteFramebuffer`].
+    pub fn set_layer(&mut self, index: u32, layer: u32) -> Result<(), GolemError> {
+        let texture = self
+            .textures
+            .get(index as usize)
+            .and_then(Option::as_ref)
+            .filter(|texture| texture.is_layered())
+            .expect("The color attachment didn't hold an array or 3D texture");
+        assert!(
+            layer < texture.layers(),
+            "The layer was past the end of the texture"
+        );
+        self.layers[index as usize] = layer;
+        self.attach_color(index);
+
+        self.check_complete()
+    }
+
+    fn attached_kind(&self, index: u32) -> Option<TextureKind> {
+        self.textures
+            .get(index as usize)
+            .and_then(Option::as_ref)
+            .map(Texture::kind)
+    }
+
+    /// Borrow the texture at the given color attachment
+    ///
+    /// # Safety
+    ///
+    /// The same 'texture loop' concerns as [`Surface::borrow_texture`] apply.
+    pub unsafe fn borrow_texture_at(&self, index: u32) -> Option<&Texture> {
+        self.textures.get(index as usize)?.as_ref()
+    }
+
+    /// Attach the texture at the index (or nothing), and draw to every attachment with a texture
+    fn attach_color(&self, index: u32) {
+        let texture = self.textures[index as usize].as_ref();
+        let layer = self.layers[index as usize];
+        let draw_buffers: Vec<u32> = self
+            .textures
+            .iter()
+            .enumerate()
+            .map(|(i, attached)| match attached {
+                Some(_) => glow::COLOR_ATTACHMENT0 + i as u32,
+                None => glow::NONE,
+            })
+            .collect();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
+            let attachment = glow::COLOR_ATTACHMENT0 + index;
+            match texture.map(Texture::kind) {
+                Some(TextureKind::CubeMap) => gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    attachment,
+                    CubeFace::ALL[layer as usize].to_gl(),
+                    texture.map(|texture| texture.id),
+                    0,
+                ),
+                // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glFramebufferTextureLayer.xhtml
+                // The layer is checked against the texture's layers by set_layer
+                Some(TextureKind::Texture2DArray) | Some(TextureKind::Texture3D) => gl
+                    .framebuffer_texture_layer(
+                        glow::FRAMEBUFFER,
+                        attachment,
+                        texture.map(|texture| texture.id),
+                        0,
+                        layer as i32,
+                    ),
+                _ => gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    attachment,
+                    glow::TEXTURE_2D,
+                    texture.map(|texture| texture.id),
+                    0,
+                ),
+            }
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glDrawBuffers.xhtml
+            // Each buffer is either NONE or the attachment at its own index, as WebGL requires
+            gl.draw_buffers(&draw_buffers);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
+        }
+    }
+
+    /// Copy the whole surface into a region of another surface, or of the window if `None`
+    ///
+    /// This is a shorthand for [`Context::blit`] with this surface as the source.
+    pub fn blit_to(
+        &self,
+        target: Option<&Surface>,
+        target_region: BlitRegion,
+        mask: BlitMask,
+        filter: BlitFilter,
+    ) {
+        let source_region =
+            BlitRegion::new(0, 0, self.width().unwrap_or(0), self.height().unwrap_or(0));
+        self.ctx.blit(
+            Some(self),
+            source_region,
+            target,
+            target_region,
+            mask,
+            filter,
+        );
+    }
+
+    /// Check that the framebuffer can be rendered to with its current attachments
+    fn check_complete(&self) -> Result<(), GolemError> {
+        check_framebuffer(&self.ctx, self.id)
     }
 
     /// Set the current render target to this surface
@@ -124,8 +499,9 @@ impl Surface {
     ///
     /// The surface must be bound first, see [`Surface::bind`].
     ///
-    /// The ColorFormat determines how many bytes each pixel is: 3 bytes for RGB and 4 for RGBA. The
-    /// slice needs have a length of `(width - x) * (height - y) * ColorFormat size`.
+    /// The ColorFormat determines how many bytes each pixel is, given by
+    /// [`ColorFormat::bytes_per_pixel`]. The slice needs have a length of
+    /// `(width - x) * (height - y) * ColorFormat size`.
     pub fn get_pixel_data(
         &self,
         x: u32,
@@ -143,13 +519,11 @@ impl Surface {
             self.has_texture(),
             "The surface had no attached image when get_pixel_data was called"
         );
-        let bytes_per_pixel = format.bytes_per_pixel();
-        let length = (width * height * bytes_per_pixel) as usize;
+        let length = format.data_size(width, height, 1);
         assert!(
-            data.len() >= length,
+            length.is_some_and(|length| data.len() >= length),
             "The buffer was not large enough to hold the data"
         );
-        let format = format.gl_format();
         let gl = &self.ctx.0.gl;
         unsafe {
             gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
@@ -158,8 +532,8 @@ impl Surface {
                 y as i32,
                 width as i32,
                 height as i32,
-                format,
-                glow::UNSIGNED_BYTE,
+                format.gl_format(),
+                format.gl_type(),
                 glow::PixelPackData::Slice(Some(data)),
             );
             gl.bind_framebuffer(glow::FRAMEBUFFER, None);
@@ -168,19 +542,297 @@ impl Surface {
 
     /// Get the width of the inner texture, or None if there is no texture
     pub fn width(&self) -> Option<u32> {
-        self.texture.as_ref().map(|tex| tex.width())
+        self.textures[0].as_ref().map(|tex| tex.width())
     }
 
     /// Get the height of the inner texture, or None if there is no texture
     pub fn height(&self) -> Option<u32> {
-        self.texture.as_ref().map(|tex| tex.height())
+        self.textures[0].as_ref().map(|tex| tex.height())
     }
 }
 
 impl Drop for Surface {
     fn drop(&mut self) {
         unsafe {
+            if let Some(DepthAttachment::Renderbuffer(renderbuffer, _)) = self.depth {
+                self.ctx.0.gl.delete_renderbuffer(renderbuffer);
+            }
             self.ctx.0.gl.delete_framebuffer(self.id);
         }
     }
 }
+
+/// A framebuffer that renders with multisample anti-aliasing
+///
+/// The samples are stored in renderbuffers, which can't be used as textures directly. Once the
+/// scene is rendered, [`MultisampleSurface::resolve_into`] averages the samples of each pixel
+/// into the texture of a regular [`Surface`].
+pub struct MultisampleSurface {
+    ctx: Context,
+    id: GlFramebuffer,
+    color: GlRenderbuffer,
+    depth: Option<GlRenderbuffer>,
+    format: ColorFormat,
+    width: u32,
+    height: u32,
+    samples: u32,
+}
+
+impl MultisampleSurface {
+    /// Create a new multisampled Surface to render to, with the given size and formats
+    ///
+    /// The number of samples per pixel is clamped to the GPU's maximum, given by
+    /// [`glow::MAX_SAMPLES`]; the number actually used is available from
+    /// [`MultisampleSurface::samples`]. Integer formats (like [`ColorFormat::R32UI`]) can't be
+    /// multisampled on WebGL.
+    ///
+    /// If the formats can't be rendered to, this returns [`GolemError::IncompleteFramebuffer`].
+    pub fn new(
+        ctx: &Context,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+        depth: Option<DepthFormat>,
+        samples: u32,
+    ) -> Result<MultisampleSurface, GolemError> {
+        assert!(width > 0, "The surface width was 0");
+        assert!(height > 0, "The surface height was 0");
+        let ctx = Context(ctx.0.clone());
+        let gl = &ctx.0.gl;
+        let max_samples = unsafe { gl.get_parameter_i32(glow::MAX_SAMPLES) } as u32;
+        let samples = samples.min(max_samples);
+        unsafe {
+            // Create every object before using any, so none of them leak if one can't be created
+            let color_buffer = gl.create_renderbuffer()?;
+            let depth_buffer = match depth.map(|_| gl.create_renderbuffer()).transpose() {
+                Ok(depth_buffer) => depth_buffer,
+                Err(error) => {
+                    gl.delete_renderbuffer(color_buffer);
+                    return Err(error.into());
+                }
+            };
+            let id = match gl.create_framebuffer() {
+                Ok(id) => id,
+                Err(error) => {
+                    gl.delete_renderbuffer(color_buffer);
+                    if let Some(depth_buffer) = depth_buffer {
+                        gl.delete_renderbuffer(depth_buffer);
+                    }
+                    return Err(error.into());
+                }
+            };
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(id));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glRenderbufferStorageMultisample.xhtml
+            // The sample count is clamped to MAX_SAMPLES above, and the format must be sized
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(color_buffer));
+            gl.renderbuffer_storage_multisample(
+                glow::RENDERBUFFER,
+                samples as i32,
+                color.gl_sized_internal_format(),
+                width as i32,
+                height as i32,
+            );
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::RENDERBUFFER,
+                Some(color_buffer),
+            );
+            if let (Some(format), Some(depth_buffer)) = (depth, depth_buffer) {
+                gl.bind_renderbuffer(glow::RENDERBUFFER, Some(depth_buffer));
+                gl.renderbuffer_storage_multisample(
+                    glow::RENDERBUFFER,
+                    samples as i32,
+                    format.gl_internal_format(),
+                    width as i32,
+                    height as i32,
+                );
+                gl.framebuffer_renderbuffer(
+                    glow::FRAMEBUFFER,
+                    format.gl_attachment(),
+                    glow::RENDERBUFFER,
+                    Some(depth_buffer),
+                );
+            }
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *ctx.0.current_surface.borrow());
+
+            let surface = MultisampleSurface {
+                ctx: Context(ctx.0.clone()),
+                id,
+                color: color_buffer,
+                depth: depth_buffer,
+                format: color,
+                width,
+                height,
+                samples,
+            };
+            check_framebuffer(&surface.ctx, surface.id)?;
+
+            Ok(surface)
+        }
+    }
+
+    /// Check if this surface is bound to be operated on
+    pub fn is_bound(&self) -> bool {
+        match *self.ctx.0.current_surface.borrow() {
+            Some(surface) => self.id == surface,
+            None => false,
+        }
+    }
+
+    /// Set the current render target to this surface
+    ///
+    /// To set the render target back to the screen, see [`Surface::unbind`].
+    pub fn bind(&self) {
+        *self.ctx.0.current_surface.borrow_mut() = Some(self.id);
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
+        }
+    }
+
+    /// Average the samples of each pixel, and write the result into the texture of the target
+    ///
+    /// The target must have a texture of the same size as this surface (which is checked for via
+    /// an `assert!`.) Only the color is resolved. The samples are copied without being converted,
+    /// so if the texture's format isn't the same as this surface's, this returns
+    /// [`GolemError::FormatMismatch`] and leaves the texture unchanged.
+    pub fn resolve_into(&self, target: &Surface) -> Result<(), GolemError> {
+        assert!(
+            target.width() == Some(self.width) && target.height() == Some(self.height),
+            "The resolve target was a different size than the multisampled surface"
+        );
+        let texture = target.textures[0]
+            .as_ref()
+            .expect("The resolve target had no texture");
+        if texture.format() != self.format {
+            return Err(GolemError::FormatMismatch {
+                expected: self.format,
+                actual: texture.format(),
+            });
+        }
+        // Resolving requires the source and target regions to be the same size
+        let region = BlitRegion::new(0, 0, self.width, self.height);
+        self.ctx.blit_framebuffer(
+            Some(self.id),
+            region,
+            Some(target.id),
+            region,
+            BlitMask::default(),
+            BlitFilter::Nearest,
+        );
+
+        Ok(())
+    }
+
+    /// The format of the color samples
+    pub fn format(&self) -> ColorFormat {
+        self.format
+    }
+
+    /// The number of samples per pixel, after clamping to the GPU's maximum
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for MultisampleSurface {
+    fn drop(&mut self) {
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.delete_renderbuffer(self.color);
+            if let Some(depth) = self.depth {
+                gl.delete_renderbuffer(depth);
+            }
+            gl.delete_framebuffer(self.id);
+        }
+    }
+}
+
+/// The error from attaching textures to a [`Surface`], which hands the textures back
+///
+/// Converts into the underlying [`GolemError`], dropping the textures, so it can be used with `?`
+/// in functions that return a `GolemError`.
+pub struct AttachmentError {
+    /// Why the textures couldn't be attached
+    pub error: GolemError,
+    /// The textures that were passed in, which aren't attached to any Surface
+    pub textures: Vec<Texture>,
+}
+
+impl AttachmentError {
+    fn new(error: GolemError, textures: Vec<Texture>) -> AttachmentError {
+        AttachmentError { error, textures }
+    }
+}
+
+impl From<AttachmentError> for GolemError {
+    fn from(other: AttachmentError) -> Self {
+        other.error
+    }
+}
+
+impl std::fmt::Debug for AttachmentError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("AttachmentError")
+            .field("error", &self.error)
+            .field("textures", &self.textures.len())
+            .finish()
+    }
+}
+
+impl std::fmt::Display for AttachmentError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.error, fmt)
+    }
+}
+
+impl std::error::Error for AttachmentError {}
+
+/// The number of color attachments a Surface can have
+fn max_color_attachments(ctx: &Context) -> i32 {
+    let gl = &ctx.0.gl;
+    unsafe {
+        gl.get_parameter_i32(glow::MAX_COLOR_ATTACHMENTS)
+            .min(gl.get_parameter_i32(glow::MAX_DRAW_BUFFERS))
+    }
+}
+
+/// Check that a framebuffer can be rendered to with its current attachments
+pub(crate) fn check_framebuffer(ctx: &Context, id: GlFramebuffer) -> Result<(), GolemError> {
+    let gl = &ctx.0.gl;
+    // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glCheckFramebufferStatus.xhtml
+    let status = unsafe {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(id));
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, *ctx.0.current_surface.borrow());
+        status
+    };
+    let reason = match status {
+        glow::FRAMEBUFFER_COMPLETE => return Ok(()),
+        glow::FRAMEBUFFER_UNDEFINED => "FRAMEBUFFER_UNDEFINED",
+        glow::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+        glow::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+            "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT"
+        }
+        glow::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => "FRAMEBUFFER_INCOMPLETE_DIMENSIONS",
+        glow::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER",
+        glow::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "FRAMEBUFFER_INCOMPLETE_READ_BUFFER",
+        glow::FRAMEBUFFER_UNSUPPORTED => "FRAMEBUFFER_UNSUPPORTED",
+        glow::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+        glow::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => "FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS",
+        _ => "unknown framebuffer status",
+    };
+    log::error!("Incomplete framebuffer: {}", reason);
+
+    Err(GolemError::IncompleteFramebuffer(reason))
+}
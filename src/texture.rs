@@ -1,29 +1,89 @@
 use super::*;
+use crate::depth::DepthFormat;
 use core::num::NonZeroU32;
+use std::vec::Vec;
 
 /// An image stored on the GPU
+///
+/// Most textures are a single 2D image, created with [`Texture::new`]. Cube maps hold six square
+/// images, one for each face of a cube, and are created with [`Texture::new_cube_map`]. Texture
+/// arrays and 3D textures hold a stack of 2D layers, and are created with [`Texture::new_array`]
+/// and [`Texture::new_3d`].
 pub struct Texture {
     ctx: Context,
     pub(crate) id: GlTexture,
+    kind: TextureKind,
+    /// Which faces of a cube map have an image, as bits in the order of [`CubeFace`]
+    faces: u8,
     width: u32,
     height: u32,
+    /// The number of layers of an array texture, or the depth of a 3D texture
+    layers: u32,
+    format: ColorFormat,
+    depth_format: Option<DepthFormat>,
+    compressed_format: Option<CompressedFormat>,
+    /// If any levels beyond the base level have been uploaded or generated
     mipmap: bool,
+    /// The value of `TEXTURE_MAX_LEVEL`, which limits sampling to the levels that exist
+    max_level: u32,
+    auto_mipmaps: bool,
 }
 
+/// The default `TEXTURE_MAX_LEVEL`, which allows every level to be sampled
+const ALL_LEVELS: u32 = 1000;
+
 impl Texture {
     /// Create a new, empty texture
     pub fn new(ctx: &Context) -> Result<Texture, GolemError> {
+        Texture::with_kind(ctx, TextureKind::Texture2D)
+    }
+
+    /// Create a new, empty cube map texture
+    ///
+    /// Each face's image is set with [`Texture::set_face_image`], and the texture is read in
+    /// shaders through a [`UniformType::SamplerCube`]. Mipmaps are generated once all six faces
+    /// have images of the same size and format.
+    pub fn new_cube_map(ctx: &Context) -> Result<Texture, GolemError> {
+        Texture::with_kind(ctx, TextureKind::CubeMap)
+    }
+
+    /// Create a new, empty 2D texture array
+    ///
+    /// The layers are all the same size and format, set with [`Texture::set_image_3d`] and
+    /// [`Texture::set_layer`], and the texture is read in shaders through a
+    /// [`UniformType::Sampler2DArray`]. Unlike a 3D texture, samples are never blended between
+    /// layers.
+    pub fn new_array(ctx: &Context) -> Result<Texture, GolemError> {
+        Texture::with_kind(ctx, TextureKind::Texture2DArray)
+    }
+
+    /// Create a new, empty 3D texture
+    ///
+    /// The image is set with [`Texture::set_image_3d`] and [`Texture::set_layer`], and the
+    /// texture is read in shaders through a [`UniformType::Sampler3D`].
+    pub fn new_3d(ctx: &Context) -> Result<Texture, GolemError> {
+        Texture::with_kind(ctx, TextureKind::Texture3D)
+    }
+
+    fn with_kind(ctx: &Context, kind: TextureKind) -> Result<Texture, GolemError> {
         let ctx = Context(ctx.0.clone());
         let id = unsafe { ctx.0.gl.create_texture()? };
         let tex = Texture {
             ctx,
             id,
+            kind,
+            faces: 0,
             width: 0,
             height: 0,
+            layers: 1,
+            format: ColorFormat::RGBA,
+            depth_format: None,
+            compressed_format: None,
             mipmap: false,
+            max_level: ALL_LEVELS,
+            auto_mipmaps: true,
         };
-        tex.set_minification(TextureFilter::Linear)
-            .expect("Linear textures don't require mip-maps");
+        tex.set_minification(TextureFilter::Linear);
 
         Ok(tex)
     }
@@ -31,85 +91,850 @@ impl Texture {
     /// Mark the texture as active, allowing it to be used in shaders
     ///
     /// To use the texture in a shader, supply the same number as the `bind_point` to a
-    /// [`UniformValue::Int`], matching a [`Uniform`] with a [`UniformType::Sampler2D`].
+    /// [`UniformValue::Int`], matching a [`Uniform`] with a [`UniformType::Sampler2D`] (or a
+    /// [`UniformType::SamplerCube`] for cube maps).
     ///
     /// The value 0 is reserved by `golem`, so it cannot be passed to this function.
+    ///
+    /// [`ShaderProgram::bind_texture`] chooses the bind point and sets the uniform automatically.
+    ///
+    /// [`ShaderProgram::bind_texture`]: crate::ShaderProgram::bind_texture
     pub fn set_active(&self, bind_point: NonZeroU32) {
+        self.bind_to_unit(bind_point.get());
+    }
+
+    /// Bind the texture to the given texture unit, leaving unit 0 active afterwards
+    pub(crate) fn bind_to_unit(&self, unit: u32) {
         let gl = &self.ctx.0.gl;
         unsafe {
-            gl.active_texture(glow::TEXTURE0 + bind_point.get());
-            gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(self.kind.to_gl(), Some(self.id));
             gl.active_texture(glow::TEXTURE0);
         }
     }
 
+    /// What kind of texture this is, which determines how it is read in shaders
+    pub fn kind(&self) -> TextureKind {
+        self.kind
+    }
+
+    /// The type of sampler uniform that reads this texture
+    pub(crate) fn sampler_type(&self) -> UniformType {
+        match self.kind {
+            TextureKind::Texture2D => match self.format {
+                ColorFormat::R32UI => UniformType::USampler2D,
+                ColorFormat::R32I => UniformType::ISampler2D,
+                _ => UniformType::Sampler2D,
+            },
+            TextureKind::CubeMap => UniformType::SamplerCube,
+            TextureKind::Texture2DArray => UniformType::Sampler2DArray,
+            TextureKind::Texture3D => UniformType::Sampler3D,
+        }
+    }
+
+    /// The width of the texture, or of each face of a cube map
     pub fn width(&self) -> u32 {
         self.width
     }
 
+    /// The height of the texture, or of each face of a cube map
     pub fn height(&self) -> u32 {
         self.height
     }
 
+    /// The number of layers of an array texture, or the depth of a 3D texture
+    ///
+    /// Other textures have a single layer.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// The format of the image data, set by the last call to [`Texture::set_image`]
+    pub fn format(&self) -> ColorFormat {
+        self.format
+    }
+
+    /// The depth format, if this texture was created by [`Texture::set_depth_image`]
+    pub fn depth_format(&self) -> Option<DepthFormat> {
+        self.depth_format
+    }
+
+    /// The compressed format, if this texture was created by [`Texture::set_compressed_image`],
+    /// [`Texture::set_compressed_face_image`], or [`Texture::set_compressed_image_3d`]
+    ///
+    /// While this is Some, [`Texture::format`] doesn't apply to the texture.
+    pub fn compressed_format(&self) -> Option<CompressedFormat> {
+        self.compressed_format
+    }
+
     /// Set the image data associated with this texture
     ///
-    /// `width` and `height` must be less than the maximum texture size of the
-    /// GPU, given by [`glow::MAX_TEXTURE_SIZE`]. If they are both powers of 2, mipmaps will be
-    /// generated. If they aren't, mipmaps will be unavailable.
+    /// `width` and `height` must be at most the maximum texture size of the
+    /// GPU, given by [`glow::MAX_TEXTURE_SIZE`]. If the format supports it, mipmaps will be
+    /// generated, unless that is turned off with [`Texture::set_auto_mipmaps`]. If not, mipmaps
+    /// will be unavailable until they are uploaded with [`Texture::set_image_level`] or
+    /// generated with [`Texture::generate_mipmaps`].
     ///
     /// If 'data' is None, the image will be created with no data at the given dimensions.
     /// If it is Some, it needs to be at least as long as `width * height *
     /// [`color.bytes_per_pixel`])
     ///
+    /// The bytes are in the native layout of the format: for example, [`ColorFormat::R32F`]
+    /// expects 4 bytes per pixel holding an `f32`. To upload typed data directly, see
+    /// [`Texture::set_image_f32`] and [`Texture::set_image_u16`].
+    ///
+    /// The texture must not be a cube map (which is checked for via an `assert!`), see
+    /// [`Texture::set_face_image`] instead.
+    ///
     /// [`color.bytes_per_pixel`]: ColorFormat::bytes_per_pixel
     pub fn set_image(&mut self, data: Option<&[u8]>, width: u32, height: u32, color: ColorFormat) {
-        assert!(width > 0, "The texture width was 0",);
-        assert!(height > 0, "The texture width was 0",);
+        if let Some(data) = data {
+            assert!(
+                color
+                    .data_size(width, height, 1)
+                    .is_some_and(|size| data.len() >= size),
+                "The texture data wasn't big enough for the width, height, and format supplied"
+            );
+        }
+        self.upload_image(None, data, width, height, color, color.gl_type());
+    }
+
+    /// Set the image data of one face of a cube map
+    ///
+    /// Each face is a square `size` pixels across, and all six faces should have the same size
+    /// and format; setting a face to a different size or format leaves the other faces to be set
+    /// again. The texture must be a cube map (which is checked for via an `assert!`), see
+    /// [`Texture::new_cube_map`]. Otherwise, this behaves like [`Texture::set_image`].
+    pub fn set_face_image(
+        &mut self,
+        face: CubeFace,
+        data: Option<&[u8]>,
+        size: u32,
+        color: ColorFormat,
+    ) {
+        if let Some(data) = data {
+            assert!(
+                color
+                    .data_size(size, size, 1)
+                    .is_some_and(|size| data.len() >= size),
+                "The texture data wasn't big enough for the size and format supplied"
+            );
+        }
+        self.upload_image(Some(face), data, size, size, color, color.gl_type());
+    }
+
+    /// Set the image data associated with this texture from floats
+    ///
+    /// The format must be one of the floating-point formats (`R16F`, `RGBA16F`, `R32F`, or
+    /// `RGBA32F`), and the data needs one `f32` for each channel of each pixel: at least
+    /// `width * height * [`color.channels`]` values. Half-float formats are converted on upload.
+    /// Otherwise, this behaves like [`Texture::set_image`].
+    ///
+    /// [`color.channels`]: ColorFormat::channels
+    pub fn set_image_f32(
+        &mut self,
+        data: Option<&[f32]>,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+    ) {
         assert!(
-            width < glow::MAX_TEXTURE_SIZE,
-            "The texture width was bigger than the maximum size"
+            color.is_float(),
+            "The texture format {:?} can't be uploaded from floats",
+            color
         );
+        if let Some(data) = data {
+            assert!(
+                data.len() >= (width * height * color.channels()) as usize,
+                "The texture data wasn't big enough for the width, height, and format supplied"
+            );
+        }
+        let data = data.map(bytemuck::cast_slice);
+        self.upload_image(None, data, width, height, color, glow::FLOAT);
+    }
+
+    /// Set the image data associated with this texture from raw half floats
+    ///
+    /// The format must be `R16F` or `RGBA16F`, and each `u16` holds the bits of one IEEE 754
+    /// half-precision float. The data needs one value for each channel of each pixel: at least
+    /// `width * height * [`color.channels`]` values. Otherwise, this behaves like
+    /// [`Texture::set_image`].
+    ///
+    /// [`color.channels`]: ColorFormat::channels
+    pub fn set_image_u16(
+        &mut self,
+        data: Option<&[u16]>,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+    ) {
         assert!(
-            height < glow::MAX_TEXTURE_SIZE,
-            "The texture height was bigger than the maximum size"
+            color.gl_type() == glow::HALF_FLOAT,
+            "The texture format {:?} can't be uploaded from half floats",
+            color
         );
         if let Some(data) = data {
             assert!(
-                data.len() >= (width * height * color.bytes_per_pixel()) as usize,
+                data.len() >= (width * height * color.channels()) as usize,
                 "The texture data wasn't big enough for the width, height, and format supplied"
             );
         }
+        let data = data.map(bytemuck::cast_slice);
+        self.upload_image(None, data, width, height, color, glow::HALF_FLOAT);
+    }
+
+    /// Check the size of a new image against the GPU's maximum
+    fn check_size(&self, width: u32, height: u32) {
+        let max_size = self.ctx.max_texture_size(self.kind);
+        assert!(
+            width <= max_size,
+            "The texture width was bigger than the maximum size"
+        );
+        assert!(
+            height <= max_size,
+            "The texture height was bigger than the maximum size"
+        );
+    }
+
+    fn upload_image(
+        &mut self,
+        face: Option<CubeFace>,
+        data: Option<&[u8]>,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+        pixel_type: u32,
+    ) {
+        let image_target = self.image_target(face);
+        assert!(width > 0, "The texture width was 0",);
+        assert!(height > 0, "The texture width was 0",);
+        self.check_size(width, height);
+        if let Some(face) = face {
+            // Faces set at a different size or format no longer match the cube
+            if self.width != width
+                || self.height != height
+                || self.format != color
+                || self.compressed_format.is_some()
+            {
+                self.faces = 0;
+            }
+            self.faces |= 1 << face as u8;
+        }
         self.width = width;
         self.height = height;
+        self.format = color;
+        self.depth_format = None;
+        self.compressed_format = None;
 
-        let format = match color {
-            ColorFormat::RGB => glow::RGB,
-            ColorFormat::RGBA => glow::RGBA,
-        };
+        let target = self.kind.to_gl();
+        let complete = face.is_none() || self.faces == ALL_FACES;
+        let generate = complete && self.auto_mipmaps && color.supports_mipmaps();
+        self.mipmap = generate;
+        self.max_level = if generate { ALL_LEVELS } else { 0 };
         let gl = &self.ctx.0.gl;
         unsafe {
-            gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
+            gl.bind_texture(target, Some(self.id));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage2D.xhtml
+            // The internal format, format, and type come from the same ColorFormat, so they are
+            // always a valid combination
             gl.tex_image_2d(
-                glow::TEXTURE_2D,
+                image_target,
                 0,
-                format as i32,
+                color.gl_internal_format() as i32,
                 width as i32,
                 height as i32,
                 0,
-                format,
-                glow::UNSIGNED_BYTE,
+                color.gl_format(),
+                pixel_type,
                 glow::PixelUnpackData::Slice(data),
             );
-            if width & (width - 1) == 0 && height & (height - 1) == 0 {
-                gl.generate_mipmap(glow::TEXTURE_2D);
-                self.mipmap = true;
+            // Until more levels exist, only the base level can be sampled
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, self.max_level as i32);
+            if generate {
+                gl.generate_mipmap(target);
+            }
+            gl.bind_texture(target, None);
+        }
+    }
+
+    /// The target to upload an image to: the texture itself, or one face of a cube map
+    fn image_target(&self, face: Option<CubeFace>) -> u32 {
+        match (self.kind, face) {
+            (TextureKind::Texture2D, None) => glow::TEXTURE_2D,
+            (TextureKind::CubeMap, Some(face)) => face.to_gl(),
+            (TextureKind::CubeMap, None) => {
+                panic!("Cube map images must be set one face at a time")
+            }
+            (TextureKind::Texture2DArray, _) | (TextureKind::Texture3D, _) => {
+                panic!("Layered texture images must be set with set_image_3d and set_layer")
+            }
+            (_, Some(_)) => panic!("Only cube maps have faces"),
+        }
+    }
+
+    /// Choose whether mipmaps are generated automatically, which they are by default
+    ///
+    /// While this is on, [`Texture::set_image`] and its siblings generate mipmaps when the
+    /// format supports it, and [`Texture::set_subimage`] and [`Texture::set_layer`] generate
+    /// them again after every change. Turning it off avoids that cost for textures that are
+    /// updated often, like atlases, or that have their levels uploaded with
+    /// [`Texture::set_image_level`]; mipmaps can then be generated when needed with
+    /// [`Texture::generate_mipmaps`].
+    pub fn set_auto_mipmaps(&mut self, auto: bool) {
+        self.auto_mipmaps = auto;
+    }
+
+    /// Generate every mipmap level from the full-size image
+    ///
+    /// This returns [`GolemError::MipMapsUnavailable`] if the texture has no image, is a cube
+    /// map without all six faces, or has a format that mipmaps can't be generated for: depth
+    /// textures, compressed textures, integer formats, and on WebGL, the float and `SRGB`
+    /// formats.
+    pub fn generate_mipmaps(&mut self) -> Result<(), GolemError> {
+        let complete = self.kind != TextureKind::CubeMap || self.faces == ALL_FACES;
+        if self.width == 0
+            || !complete
+            || self.depth_format.is_some()
+            || self.compressed_format.is_some()
+            || !self.format.can_generate_mipmaps()
+        {
+            return Err(GolemError::MipMapsUnavailable);
+        }
+        self.mipmap = true;
+        self.max_level = ALL_LEVELS;
+        let target = self.kind.to_gl();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, ALL_LEVELS as i32);
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glGenerateMipmap.xhtml
+            // Errors:
+            // 1. The target is the texture's own kind
+            // 2. Incomplete cube maps and unsupported formats are ruled out above
+            gl.generate_mipmap(target);
+            gl.bind_texture(target, None);
+        }
+
+        Ok(())
+    }
+
+    /// Set the image data of one mipmap level
+    ///
+    /// Level 0 is the full-size image, set by [`Texture::set_image`] or
+    /// [`Texture::set_image_3d`], which must be set first; each level after it is half the
+    /// width and height of the one before, rounded down but at least 1 pixel. The levels of 3D
+    /// textures also have half the layers, while arrays keep all of their layers. The data is
+    /// laid out like the full-size image, in the same format, and needs to be big enough for
+    /// the size of the level.
+    ///
+    /// Levels should be set in order, starting from 1; only the levels up to the last one set
+    /// are sampled, so the chain doesn't need to go all the way down to 1x1. To keep the levels
+    /// from being replaced by generated ones, turn off [`Texture::set_auto_mipmaps`] before
+    /// setting the full-size image. Compressed textures have their levels set all at once, by
+    /// [`Texture::set_compressed_image`].
+    ///
+    /// The texture must not be a cube map (which is checked for via an `assert!`), see
+    /// [`Texture::set_face_image_level`] instead.
+    pub fn set_image_level(&mut self, level: u32, data: &[u8]) {
+        self.upload_level(None, level, data);
+    }
+
+    /// Set the image data of one mipmap level of one face of a cube map
+    ///
+    /// The texture must be a cube map (which is checked for via an `assert!`). Otherwise, this
+    /// behaves like [`Texture::set_image_level`].
+    pub fn set_face_image_level(&mut self, face: CubeFace, level: u32, data: &[u8]) {
+        self.upload_level(Some(face), level, data);
+    }
+
+    /// Upload one mipmap level beyond the full-size image, which must already be set
+    pub(crate) fn upload_level(&mut self, face: Option<CubeFace>, level: u32, data: &[u8]) {
+        assert!(
+            self.width > 0,
+            "The full-size image must be set before the other levels"
+        );
+        assert!(
+            self.depth_format.is_none() && self.compressed_format.is_none(),
+            "Only uncompressed color textures can have their levels set"
+        );
+        assert!(level > 0, "Level 0 is set along with the image size");
+        let depth = if self.kind == TextureKind::Texture3D {
+            self.layers
+        } else {
+            1
+        };
+        let max_levels = 32 - self.width.max(self.height).max(depth).leading_zeros();
+        assert!(level < max_levels, "The level was smaller than 1x1");
+        let width = (self.width >> level).max(1);
+        let height = (self.height >> level).max(1);
+        let layers = match self.kind {
+            TextureKind::Texture3D => (self.layers >> level).max(1),
+            _ => self.layers,
+        };
+        let color = self.format;
+        assert!(
+            color
+                .data_size(width, height, layers)
+                .is_some_and(|size| data.len() >= size),
+            "The data for level {} wasn't big enough for its size and format",
+            level
+        );
+        self.mipmap = true;
+        if self.max_level < level {
+            self.max_level = level;
+        }
+
+        let target = self.kind.to_gl();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage2D.xhtml
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage3D.xhtml
+            // The level is within the maximum for the image size, and the internal format,
+            // format, and type all come from the format of the full-size image
+            if self.is_layered() {
+                gl.tex_image_3d(
+                    target,
+                    level as i32,
+                    color.gl_internal_format() as i32,
+                    width as i32,
+                    height as i32,
+                    layers as i32,
+                    0,
+                    color.gl_format(),
+                    color.gl_type(),
+                    glow::PixelUnpackData::Slice(Some(data)),
+                );
             } else {
-                self.mipmap = false;
-                self.set_wrap_h(TextureWrap::ClampToEdge)
-                    .expect("The texture wrap ClampToEdge is always valid");
-                self.set_wrap_v(TextureWrap::ClampToEdge)
-                    .expect("The texture wrap ClampToEdge is always valid");
+                gl.tex_image_2d(
+                    self.image_target(face),
+                    level as i32,
+                    color.gl_internal_format() as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    color.gl_format(),
+                    color.gl_type(),
+                    glow::PixelUnpackData::Slice(Some(data)),
+                );
+            }
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, self.max_level as i32);
+            gl.bind_texture(target, None);
+        }
+    }
+
+    /// Set the image data of this texture from block-compressed data
+    ///
+    /// `levels` holds the mipmap levels, starting with the full-size image, each laid out as
+    /// [`format.data_size`] bytes of compressed blocks. Each level is half the width and height
+    /// of the one before, rounded down but at least 1 pixel. If more than one level is given,
+    /// mipmaps are available; they are never generated for compressed textures. Levels past
+    /// the last one given are never sampled, so the chain doesn't need to go all the way down
+    /// to 1x1.
+    ///
+    /// If the context doesn't support the format (see [`Context::supports_compressed_format`]),
+    /// this returns [`GolemError::ExtensionUnavailable`] with the name of an extension that
+    /// provides it. If a level's data is shorter than its size needs, this returns
+    /// [`GolemError::InvalidImageData`]. Either way, the texture is left unchanged. Compressed
+    /// textures can't be updated with [`Texture::set_subimage`] or read back with
+    /// [`Texture::read_pixels`], and most GPUs require the width and height to be multiples of
+    /// the block size.
+    ///
+    /// `width` and `height` have the same limits as [`Texture::set_image`], and the texture must
+    /// not be a cube map (which is checked for via an `assert!`), see
+    /// [`Texture::set_compressed_face_image`] instead.
+    ///
+    /// [`format.data_size`]: CompressedFormat::data_size
+    pub fn set_compressed_image(
+        &mut self,
+        levels: &[&[u8]],
+        width: u32,
+        height: u32,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        self.upload_compressed(None, levels, width, height, format)
+    }
+
+    /// Set the image data of one face of a cube map from block-compressed data
+    ///
+    /// Faces are tracked as in [`Texture::set_face_image`], and mipmaps are available once all
+    /// six faces have the same size and format. Every face should be given the same number of
+    /// levels. Otherwise, this behaves like
+    /// [`Texture::set_compressed_image`].
+    pub fn set_compressed_face_image(
+        &mut self,
+        face: CubeFace,
+        levels: &[&[u8]],
+        size: u32,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        self.upload_compressed(Some(face), levels, size, size, format)
+    }
+
+    fn upload_compressed(
+        &mut self,
+        face: Option<CubeFace>,
+        levels: &[&[u8]],
+        width: u32,
+        height: u32,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        let image_target = self.image_target(face);
+        self.check_compressed(levels, width, height, 1, format)?;
+        if let Some(face) = face {
+            if self.width != width
+                || self.height != height
+                || self.compressed_format != Some(format)
+            {
+                self.faces = 0;
+            }
+            self.faces |= 1 << face as u8;
+        }
+        self.width = width;
+        self.height = height;
+        self.depth_format = None;
+        self.compressed_format = Some(format);
+        let complete = face.is_none() || self.faces == ALL_FACES;
+        self.mipmap = complete && levels.len() > 1;
+        self.max_level = levels.len() as u32 - 1;
+
+        let target = self.kind.to_gl();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            for (level, data) in levels.iter().enumerate() {
+                let level_width = (width >> level).max(1);
+                let level_height = (height >> level).max(1);
+                let size = format
+                    .data_size(level_width, level_height)
+                    .expect("The size was checked by check_compressed");
+                // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glCompressedTexImage2D.xhtml
+                // Errors:
+                // 1. The format is supported, which is checked above
+                // 2. The image size matches the dimensions and format, because it comes from the
+                //    format's block size, and the data is at least that long, which is also
+                //    checked above
+                // 3. Dimensions which aren't a multiple of the block size are rejected by some
+                //    GPUs, which the GL reports
+                gl.compressed_tex_image_2d(
+                    image_target,
+                    level as i32,
+                    format.gl_internal_format() as i32,
+                    level_width as i32,
+                    level_height as i32,
+                    0,
+                    size as i32,
+                    &data[..size],
+                );
             }
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, self.max_level as i32);
+            gl.bind_texture(target, None);
+        }
+
+        Ok(())
+    }
+
+    /// Check the support, size, number of levels, and data length of a compressed image
+    ///
+    /// This runs before anything about the texture changes, so it is left as it was on an error.
+    fn check_compressed(
+        &self,
+        levels: &[&[u8]],
+        width: u32,
+        height: u32,
+        layers: u32,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        if !self.ctx.supports_compressed_format(format) {
+            // Formats without extensions are always supported, so one is always found here
+            if let Some(extension) = format.extensions().first() {
+                return Err(GolemError::ExtensionUnavailable(extension));
+            }
+        }
+        assert!(width > 0, "The texture width was 0",);
+        assert!(height > 0, "The texture height was 0",);
+        self.check_size(width, height);
+        assert!(!levels.is_empty(), "No compressed image levels were given");
+        let max_levels = 32 - width.max(height).leading_zeros();
+        assert!(
+            levels.len() as u32 <= max_levels,
+            "More levels were given than a {}x{} image can have",
+            width,
+            height
+        );
+        for (level, data) in levels.iter().enumerate() {
+            let level_width = (width >> level).max(1);
+            let level_height = (height >> level).max(1);
+            let size = format
+                .data_size(level_width, level_height)
+                .and_then(|size| size.checked_mul(layers as usize));
+            match size {
+                Some(size) if data.len() >= size => {}
+                _ => {
+                    return Err(GolemError::InvalidImageData(format!(
+                        "level {} was {} bytes, too short for {} {}x{} layers of {:?}",
+                        level,
+                        data.len(),
+                        layers,
+                        level_width,
+                        level_height,
+                        format
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the image data of an array or 3D texture
+    ///
+    /// The data is laid out one layer after another, with each layer laid out like the data of
+    /// [`Texture::set_image`]. If it is Some, it needs to be at least as long as
+    /// `width * height * layers * [`color.bytes_per_pixel`]`. If it is None, the layers are
+    /// created with no data, to be filled by [`Texture::set_layer`].
+    ///
+    /// `width` and `height` have the same limits as [`Texture::set_image`], and `layers` must be
+    /// at most [`glow::MAX_ARRAY_TEXTURE_LAYERS`] for arrays or [`glow::MAX_3D_TEXTURE_SIZE`]
+    /// for 3D textures. Mipmaps are generated like [`Texture::set_image`], and only the width
+    /// and height of arrays get smaller at each level. The texture must be an array or 3D
+    /// texture (which is checked for via an `assert!`.)
+    ///
+    /// [`color.bytes_per_pixel`]: ColorFormat::bytes_per_pixel
+    pub fn set_image_3d(
+        &mut self,
+        data: Option<&[u8]>,
+        width: u32,
+        height: u32,
+        layers: u32,
+        color: ColorFormat,
+    ) {
+        assert!(self.is_layered(), "Only array and 3D textures have layers");
+        assert!(width > 0, "The texture width was 0",);
+        assert!(height > 0, "The texture height was 0",);
+        assert!(layers > 0, "The texture had 0 layers",);
+        self.check_size(width, height);
+        assert!(
+            layers <= self.ctx.max_texture_layers(self.kind),
+            "The texture had more layers than the maximum"
+        );
+        let gl = &self.ctx.0.gl;
+        let target = self.kind.to_gl();
+        if let Some(data) = data {
+            assert!(
+                color
+                    .data_size(width, height, layers)
+                    .is_some_and(|size| data.len() >= size),
+                "The texture data wasn't big enough for the width, height, layers, and format supplied"
+            );
+        }
+        self.width = width;
+        self.height = height;
+        self.layers = layers;
+        self.format = color;
+        self.depth_format = None;
+        self.compressed_format = None;
+        let generate = self.auto_mipmaps && color.supports_mipmaps();
+        self.mipmap = generate;
+        self.max_level = if generate { ALL_LEVELS } else { 0 };
+
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage3D.xhtml
+            // The internal format, format, and type come from the same ColorFormat, so they are
+            // always a valid combination
+            gl.tex_image_3d(
+                target,
+                0,
+                color.gl_internal_format() as i32,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                color.gl_format(),
+                color.gl_type(),
+                glow::PixelUnpackData::Slice(data),
+            );
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, self.max_level as i32);
+            if generate {
+                gl.generate_mipmap(target);
+            }
+            gl.bind_texture(target, None);
+        }
+    }
+
+    /// Set the image data of a texture array from block-compressed data
+    ///
+    /// Each entry of `levels` holds one mipmap level of every layer, one layer after another,
+    /// with each layer laid out like the levels of [`Texture::set_compressed_image`]. `layers`
+    /// has the same limits as [`Texture::set_image_3d`], and the errors are the same as
+    /// [`Texture::set_compressed_image`]. The texture must be an array (which is checked for via
+    /// an `assert!`), because compressed 3D textures aren't available on every platform.
+    pub fn set_compressed_image_3d(
+        &mut self,
+        levels: &[&[u8]],
+        width: u32,
+        height: u32,
+        layers: u32,
+        format: CompressedFormat,
+    ) -> Result<(), GolemError> {
+        assert!(
+            self.kind == TextureKind::Texture2DArray,
+            "Only texture arrays can have compressed layers"
+        );
+        assert!(layers > 0, "The texture had 0 layers",);
+        assert!(
+            layers <= self.ctx.max_texture_layers(self.kind),
+            "The texture had more layers than the maximum"
+        );
+        let gl = &self.ctx.0.gl;
+        let target = self.kind.to_gl();
+        self.check_compressed(levels, width, height, layers, format)?;
+        self.width = width;
+        self.height = height;
+        self.layers = layers;
+        self.depth_format = None;
+        self.compressed_format = Some(format);
+        self.mipmap = levels.len() > 1;
+        self.max_level = levels.len() as u32 - 1;
+
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            for (level, data) in levels.iter().enumerate() {
+                let level_width = (width >> level).max(1);
+                let level_height = (height >> level).max(1);
+                // The size can't overflow, because check_compressed checks it
+                let size = format
+                    .data_size(level_width, level_height)
+                    .expect("The size was checked by check_compressed")
+                    * layers as usize;
+                // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glCompressedTexImage3D.xhtml
+                // The errors are the same as for compressed 2D images in upload_compressed, and
+                // only arrays are allowed
+                gl.compressed_tex_image_3d(
+                    target,
+                    level as i32,
+                    format.gl_internal_format() as i32,
+                    level_width as i32,
+                    level_height as i32,
+                    layers as i32,
+                    0,
+                    size as i32,
+                    &data[..size],
+                );
+            }
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, self.max_level as i32);
+            gl.bind_texture(target, None);
+        }
+
+        Ok(())
+    }
+
+    /// Set the image data of one layer of an array or 3D texture
+    ///
+    /// The data covers the whole layer, so it needs to be at least as long as
+    /// `width * height * [`color.bytes_per_pixel`]`, and the format should match the one given
+    /// to [`Texture::set_image_3d`]. The layer must be less than [`Texture::layers`], and the
+    /// texture must be an array or 3D texture (both checked for via an `assert!`.)
+    ///
+    /// [`color.bytes_per_pixel`]: ColorFormat::bytes_per_pixel
+    pub fn set_layer(&self, layer: u32, data: &[u8], color: ColorFormat) {
+        assert!(self.is_layered(), "Only array and 3D textures have layers");
+        assert!(
+            self.compressed_format.is_none(),
+            "Compressed textures can't be updated in part"
+        );
+        assert!(
+            layer < self.layers,
+            "The layer was past the end of the texture"
+        );
+        assert!(
+            color
+                .data_size(self.width, self.height, 1)
+                .is_some_and(|size| data.len() >= size),
+            "The texture data wasn't big enough for the layer"
+        );
+        let target = self.kind.to_gl();
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(target, Some(self.id));
+            gl.tex_sub_image_3d(
+                target,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width as i32,
+                self.height as i32,
+                1,
+                color.gl_format(),
+                color.gl_type(),
+                glow::PixelUnpackData::Slice(Some(data)),
+            );
+            if self.mipmap && self.auto_mipmaps {
+                gl.generate_mipmap(target);
+            }
+            gl.bind_texture(target, None);
+        }
+    }
+
+    /// If this texture is a stack of layers
+    pub(crate) fn is_layered(&self) -> bool {
+        matches!(
+            self.kind,
+            TextureKind::Texture2DArray | TextureKind::Texture3D
+        )
+    }
+
+    /// Make this an empty depth texture, to attach to a [`Surface`] as its depth buffer
+    ///
+    /// Once rendered to, the depth values can be read in a shader through a
+    /// [`UniformType::Sampler2D`], for example to sample a shadow map. Depth textures have no
+    /// mipmaps, and use `Nearest` filtering.
+    ///
+    /// `width` and `height` have the same limits as [`Texture::set_image`], and should match the
+    /// size of the Surface's color texture.
+    pub fn set_depth_image(&mut self, width: u32, height: u32, format: DepthFormat) {
+        assert!(width > 0, "The texture width was 0",);
+        assert!(height > 0, "The texture height was 0",);
+        self.check_size(width, height);
+        assert!(
+            self.kind == TextureKind::Texture2D,
+            "Only 2D textures can be depth textures"
+        );
+        self.width = width;
+        self.height = height;
+        self.depth_format = Some(format);
+        self.compressed_format = None;
+        self.mipmap = false;
+        self.max_level = 0;
+
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAX_LEVEL, 0);
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage2D.xhtml
+            // The internal format, format, and type come from the same DepthFormat, so they are
+            // always a valid combination
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                format.gl_internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                format.gl_format(),
+                format.gl_type(),
+                glow::PixelUnpackData::Slice(None),
+            );
+            // Depth textures aren't filterable on WebGL
+            self.set_minification(TextureFilter::Nearest);
+            self.set_magnification(TextureFilter::Nearest)
+                .expect("Nearest textures don't require mip-maps");
+            self.set_wrap_h(TextureWrap::ClampToEdge)
+                .expect("The texture wrap ClampToEdge is always valid");
+            self.set_wrap_v(TextureWrap::ClampToEdge)
+                .expect("The texture wrap ClampToEdge is always valid");
             gl.bind_texture(glow::TEXTURE_2D, None);
         }
     }
@@ -119,6 +944,9 @@ impl Texture {
     /// The data provided must be enough to cover `width * height * [`color.bytes_per_pixel()`]`.
     /// Also, the region must be within the texture's bounds.
     ///
+    /// The texture must not be a cube map (which is checked for via an `assert!`), see
+    /// [`Texture::set_face_subimage`] instead.
+    ///
     /// [`color.bytes_per_pixel()`]: ColorFormat::bytes_per_pixel
     pub fn set_subimage(
         &self,
@@ -129,6 +957,43 @@ impl Texture {
         height: u32,
         color: ColorFormat,
     ) {
+        self.upload_subimage(None, data, x, y, width, height, color);
+    }
+
+    /// Set a region of one face of a cube map
+    ///
+    /// The texture must be a cube map (which is checked for via an `assert!`). Otherwise, this
+    /// behaves like [`Texture::set_subimage`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_face_subimage(
+        &self,
+        face: CubeFace,
+        data: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+    ) {
+        self.upload_subimage(Some(face), data, x, y, width, height, color);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn upload_subimage(
+        &self,
+        face: Option<CubeFace>,
+        data: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: ColorFormat,
+    ) {
+        let image_target = self.image_target(face);
+        assert!(
+            self.compressed_format.is_none(),
+            "Compressed textures can't be updated in part"
+        );
         assert!(
             x + width <= self.width,
             "The region over-ran the width of the texture"
@@ -137,49 +1002,187 @@ impl Texture {
             y + height <= self.height,
             "The region over-ran the height of the texture"
         );
-        let format = match color {
-            ColorFormat::RGB => glow::RGB,
-            ColorFormat::RGBA => glow::RGBA,
-        };
-        let required_data_len = width * height * color.bytes_per_pixel();
-        assert!(data.len() >= required_data_len as usize);
+        assert!(
+            color
+                .data_size(width, height, 1)
+                .is_some_and(|size| data.len() >= size),
+            "The texture data wasn't big enough for the region and format supplied"
+        );
+        let target = self.kind.to_gl();
         let gl = &self.ctx.0.gl;
         unsafe {
-            gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
+            gl.bind_texture(target, Some(self.id));
             gl.tex_sub_image_2d(
-                glow::TEXTURE_2D,
+                image_target,
                 0,
                 x as i32,
                 y as i32,
                 width as i32,
                 height as i32,
-                format,
-                glow::UNSIGNED_BYTE,
+                color.gl_format(),
+                color.gl_type(),
                 glow::PixelUnpackData::Slice(Some(data)),
             );
-            gl.generate_mipmap(glow::TEXTURE_2D);
-            gl.bind_texture(glow::TEXTURE_2D, None);
+            if self.mipmap && self.auto_mipmaps {
+                gl.generate_mipmap(target);
+            }
+            gl.bind_texture(target, None);
         }
     }
 
+    /// Read the pixels of the texture back from the GPU
+    ///
+    /// The pixels are laid out like the data given to [`Texture::set_image`], with
+    /// [`ColorFormat::bytes_per_pixel`] bytes per pixel, starting from the bottom row. The texture
+    /// must be a 2D texture with one of the 8-bit formats (`RGB`, `RGBA`, `R8`, `RG8`, `SRGB`, or
+    /// `SRGBA`), which is checked for via an `assert!`. For other formats, see
+    /// [`Texture::read_pixels_f32`], [`Texture::read_pixels_u32`], and
+    /// [`Texture::read_pixels_i32`].
+    ///
+    /// This waits for all rendering to the texture to finish, so it is slow. The texture doesn't
+    /// need to be part of a [`Surface`]: a temporary framebuffer is used to read it. If that
+    /// framebuffer isn't complete (for example if the texture has no image), this returns
+    /// [`GolemError::IncompleteFramebuffer`].
+    pub fn read_pixels(&self) -> Result<Vec<u8>, GolemError> {
+        assert!(
+            self.format.gl_type() == glow::UNSIGNED_BYTE,
+            "The texture format {:?} can't be read as bytes",
+            self.format
+        );
+        self.read_rgba(glow::RGBA, glow::UNSIGNED_BYTE)
+    }
+
+    /// Read the pixels of a floating-point texture back from the GPU
+    ///
+    /// The texture must be a 2D texture with one of the floating-point formats (which is checked
+    /// for via an `assert!`), and half floats are converted to `f32`. There are
+    /// [`ColorFormat::channels`] values per pixel. Otherwise, this behaves like
+    /// [`Texture::read_pixels`].
+    ///
+    /// Float textures can only be read on WebGL when `EXT_color_buffer_float` is available;
+    /// otherwise this returns [`GolemError::IncompleteFramebuffer`].
+    pub fn read_pixels_f32(&self) -> Result<Vec<f32>, GolemError> {
+        assert!(
+            self.format.is_float(),
+            "The texture format {:?} can't be read as floats",
+            self.format
+        );
+        self.read_rgba(glow::RGBA, glow::FLOAT)
+    }
+
+    /// Read the pixels of an [`ColorFormat::R32UI`] texture back from the GPU
+    ///
+    /// The texture must be a 2D texture with the `R32UI` format (which is checked for via an
+    /// `assert!`.) Otherwise, this behaves like [`Texture::read_pixels`].
+    pub fn read_pixels_u32(&self) -> Result<Vec<u32>, GolemError> {
+        assert!(
+            self.format == ColorFormat::R32UI,
+            "The texture format {:?} can't be read as unsigned integers",
+            self.format
+        );
+        self.read_rgba(glow::RGBA_INTEGER, glow::UNSIGNED_INT)
+    }
+
+    /// Read the pixels of an [`ColorFormat::R32I`] texture back from the GPU
+    ///
+    /// The texture must be a 2D texture with the `R32I` format (which is checked for via an
+    /// `assert!`.) Otherwise, this behaves like [`Texture::read_pixels`].
+    pub fn read_pixels_i32(&self) -> Result<Vec<i32>, GolemError> {
+        assert!(
+            self.format == ColorFormat::R32I,
+            "The texture format {:?} can't be read as signed integers",
+            self.format
+        );
+        self.read_rgba(glow::RGBA_INTEGER, glow::INT)
+    }
+
+    /// Read the texture as 4 channels per pixel, then keep only the channels of its format
+    ///
+    /// Reading 4 channels of the format's base type is the only combination guaranteed to be
+    /// supported everywhere.
+    fn read_rgba<T: bytemuck::Pod>(
+        &self,
+        format: u32,
+        pixel_type: u32,
+    ) -> Result<Vec<T>, GolemError> {
+        assert!(
+            self.kind == TextureKind::Texture2D
+                && self.depth_format.is_none()
+                && self.compressed_format.is_none(),
+            "Only uncompressed 2D color textures can be read"
+        );
+        let pixels = (self.width * self.height) as usize;
+        let mut rgba = vec![T::zeroed(); pixels * 4];
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            let framebuffer = gl.create_framebuffer()?;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.id),
+                0,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
+            if let Err(error) = crate::surface::check_framebuffer(&self.ctx, framebuffer) {
+                gl.delete_framebuffer(framebuffer);
+                return Err(error);
+            }
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            // https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glReadPixels.xhtml
+            // Errors:
+            // 1. The format and type are the combination always supported for the texture format
+            // 2. The buffer holds 4 channels for every pixel of the texture
+            // 3. Formats that can't be rendered to (like some float formats) leave the framebuffer
+            //    incomplete, which is checked for above
+            gl.read_pixels(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                format,
+                pixel_type,
+                glow::PixelPackData::Slice(Some(bytemuck::cast_slice_mut(&mut rgba))),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, *self.ctx.0.current_surface.borrow());
+            gl.delete_framebuffer(framebuffer);
+        }
+        let channels = self.format.channels() as usize;
+        if channels == 4 {
+            return Ok(rgba);
+        }
+
+        Ok(rgba
+            .chunks(4)
+            .flat_map(|pixel| pixel[..channels].iter().copied())
+            .collect())
+    }
+
     fn set_texture_param(&self, param: u32, value: i32) {
         let gl = &self.ctx.0.gl;
         unsafe {
-            gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
-            gl.tex_parameter_i32(glow::TEXTURE_2D, param, value);
+            gl.bind_texture(self.kind.to_gl(), Some(self.id));
+            gl.tex_parameter_i32(self.kind.to_gl(), param, value);
         }
     }
 
-    /// Determine how the texture should scale down
-    pub fn set_minification(&self, min: TextureFilter) -> Result<(), GolemError> {
-        if !self.mipmap && min.uses_mipmap() {
-            Err(GolemError::MipMapsUnavailable)
-        } else {
-            self.set_texture_param(glow::TEXTURE_MIN_FILTER, min.to_gl());
-            Ok(())
+    fn set_texture_param_f32(&self, param: u32, value: f32) {
+        let gl = &self.ctx.0.gl;
+        unsafe {
+            gl.bind_texture(self.kind.to_gl(), Some(self.id));
+            gl.tex_parameter_f32(self.kind.to_gl(), param, value);
         }
     }
 
+    /// Determine how the texture should scale down
+    ///
+    /// Any filter can be used: until mipmaps are uploaded or generated, the filters that use
+    /// mipmaps only read the full-size image.
+    pub fn set_minification(&self, min: TextureFilter) {
+        self.set_texture_param(glow::TEXTURE_MIN_FILTER, min.to_gl());
+    }
+
     /// Determine how the texture should scale up
     pub fn set_magnification(&self, max: TextureFilter) -> Result<(), GolemError> {
         if max.uses_mipmap() {
@@ -191,26 +1194,113 @@ impl Texture {
     }
 
     /// Determine how the texture is wrapped horizontally
+    ///
+    /// Every wrap can be used with every texture, including textures whose sizes aren't powers
+    /// of 2. The only error is [`GolemError::IllegalWrapOption`] for
+    /// [`TextureWrap::ClampToBorder`] on the web.
     pub fn set_wrap_h(&self, wrap: TextureWrap) -> Result<(), GolemError> {
-        if !self.mipmap && wrap != TextureWrap::ClampToEdge {
-            Err(GolemError::IllegalWrapOption)
-        } else {
-            self.set_texture_param(glow::TEXTURE_WRAP_S, wrap.to_gl());
-            Ok(())
-        }
+        self.set_texture_param(glow::TEXTURE_WRAP_S, wrap.to_gl()?);
+        Ok(())
     }
 
     /// Determine how the texture is wrapped vertically
+    ///
+    /// The errors are the same as [`Texture::set_wrap_h`].
     pub fn set_wrap_v(&self, wrap: TextureWrap) -> Result<(), GolemError> {
-        if !self.mipmap && wrap != TextureWrap::ClampToEdge {
-            Err(GolemError::IllegalWrapOption)
-        } else {
-            self.set_texture_param(glow::TEXTURE_WRAP_T, wrap.to_gl());
-            Ok(())
+        self.set_texture_param(glow::TEXTURE_WRAP_T, wrap.to_gl()?);
+        Ok(())
+    }
+
+    /// Sample the texture more times when it's viewed at a steep angle, up to `level` times
+    ///
+    /// This keeps textures sharp at grazing angles, like a floor seen from above. It requires
+    /// the `EXT_texture_filter_anisotropic` extension, and returns
+    /// [`GolemError::ExtensionUnavailable`] without it. The level must be between 1 (the
+    /// default, which turns it off) and [`Context::max_anisotropy`], or the function will
+    /// panic.
+    pub fn set_anisotropy(&self, level: f32) -> Result<(), GolemError> {
+        self.ctx.check_anisotropy(level)?;
+        self.set_texture_param_f32(glow::TEXTURE_MAX_ANISOTROPY_EXT, level);
+
+        Ok(())
+    }
+
+    /// Limit which mipmap levels are used, from `min` (the most detailed) to `max`
+    ///
+    /// The levels may be fractional. The defaults are `-1000.0` and `1000.0`, i.e. "all
+    /// levels are used".
+    pub fn set_lod_range(&self, min: f32, max: f32) {
+        assert!(min <= max, "The minimum LOD was above the maximum");
+        self.set_texture_param_f32(glow::TEXTURE_MIN_LOD, min);
+        self.set_texture_param_f32(glow::TEXTURE_MAX_LOD, max);
+    }
+
+    /// Offset the mipmap level chosen when sampling, where positive values are blurrier
+    ///
+    /// LOD bias is only available on desktop; on the web, shaders can pass a bias to `texture`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_lod_bias(&self, bias: f32) {
+        self.set_texture_param_f32(glow::TEXTURE_LOD_BIAS, bias);
+    }
+}
+
+/// The shape of a texture, and how it is read in shaders
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum TextureKind {
+    /// A single 2D image, read through a [`UniformType::Sampler2D`]
+    Texture2D,
+    /// Six square images forming the faces of a cube, read through a
+    /// [`UniformType::SamplerCube`] with a direction vector
+    CubeMap,
+    /// A stack of separate 2D images, read through a [`UniformType::Sampler2DArray`] with the
+    /// layer index as the third coordinate
+    Texture2DArray,
+    /// A volume of pixels, read through a [`UniformType::Sampler3D`] with three coordinates
+    Texture3D,
+}
+
+impl TextureKind {
+    pub(crate) fn to_gl(self) -> u32 {
+        match self {
+            TextureKind::Texture2D => glow::TEXTURE_2D,
+            TextureKind::CubeMap => glow::TEXTURE_CUBE_MAP,
+            TextureKind::Texture2DArray => glow::TEXTURE_2D_ARRAY,
+            TextureKind::Texture3D => glow::TEXTURE_3D,
         }
     }
 }
 
+/// The bits of `Texture::faces` when every face of a cube map has an image
+const ALL_FACES: u8 = 0b11_1111;
+
+/// One face of a cube map texture
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    /// Every face of a cube map, in order
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    pub(crate) fn to_gl(self) -> u32 {
+        // The face targets are consecutive, in the same order as this enum
+        glow::TEXTURE_CUBE_MAP_POSITIVE_X + self as u32
+    }
+}
+
 /// How textures should scale when being drawn at non-native sizes
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum TextureFilter {
@@ -246,7 +1336,7 @@ impl TextureFilter {
 
     /// If this texture filter uses texture mipmaps
     ///
-    /// Mipmaps are only available for power-of-two textures, and only available for minification
+    /// Mipmaps are only available for minification
     pub fn uses_mipmap(self) -> bool {
         !matches!(self, TextureFilter::Linear | TextureFilter::Nearest)
     }
@@ -261,14 +1351,27 @@ pub enum TextureWrap {
     ClampToEdge,
     /// Repeat as though the texture was endlessly tiled, but flipping each time
     MirroredRepeat,
+    /// Use the border color, set by [`Sampler::set_border_color`]
+    ///
+    /// Border colors are only available on desktop: on the web, setting this wrap returns
+    /// [`GolemError::IllegalWrapOption`].
+    ///
+    /// [`Sampler::set_border_color`]: crate::Sampler::set_border_color
+    ClampToBorder,
 }
 
 impl TextureWrap {
-    pub(crate) fn to_gl(self) -> i32 {
+    /// The GL constant for the wrap, or an error if it isn't available on this platform
+    pub(crate) fn to_gl(self) -> Result<i32, GolemError> {
         match self {
-            TextureWrap::Repeat => glow::REPEAT as i32,
-            TextureWrap::ClampToEdge => glow::CLAMP_TO_EDGE as i32,
-            TextureWrap::MirroredRepeat => glow::MIRRORED_REPEAT as i32,
+            TextureWrap::Repeat => Ok(glow::REPEAT as i32),
+            TextureWrap::ClampToEdge => Ok(glow::CLAMP_TO_EDGE as i32),
+            TextureWrap::MirroredRepeat => Ok(glow::MIRRORED_REPEAT as i32),
+            // WebGL 2 has no border colors
+            TextureWrap::ClampToBorder if cfg!(target_arch = "wasm32") => {
+                Err(GolemError::IllegalWrapOption)
+            }
+            TextureWrap::ClampToBorder => Ok(glow::CLAMP_TO_BORDER as i32),
         }
     }
 }
@@ -280,3 +1383,19 @@ impl Drop for Texture {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_wrap_is_only_available_on_desktop() {
+        let border = TextureWrap::ClampToBorder.to_gl();
+        if cfg!(target_arch = "wasm32") {
+            assert!(matches!(border, Err(GolemError::IllegalWrapOption)));
+        } else {
+            assert_eq!(border.ok(), Some(glow::CLAMP_TO_BORDER as i32));
+        }
+        assert_eq!(TextureWrap::Repeat.to_gl().ok(), Some(glow::REPEAT as i32));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{Dimension, NumberType};
+use crate::{Dimension, Dimension::*, GolemError, NumberType};
+use std::borrow::ToOwned;
 use std::boxed::Box;
 use std::format;
 use std::string::String;
@@ -25,6 +26,7 @@ impl Uniform {
 }
 
 /// The type of the uniform in GLSL
+#[derive(Clone, PartialEq, Eq)]
 pub enum UniformType {
     /// Either an `int` or a `float`
     Scalar(NumberType),
@@ -34,6 +36,38 @@ pub enum UniformType {
     Matrix(Dimension),
     /// A texutre binding
     Sampler2D,
+    /// A cube map texture binding, see [`Texture::new_cube_map`]
+    ///
+    /// [`Texture::new_cube_map`]: crate::Texture::new_cube_map
+    SamplerCube,
+    /// An array texture binding, see [`Texture::new_array`]
+    ///
+    /// [`Texture::new_array`]: crate::Texture::new_array
+    Sampler2DArray,
+    /// A 3D texture binding, see [`Texture::new_3d`]
+    ///
+    /// [`Texture::new_3d`]: crate::Texture::new_3d
+    Sampler3D,
+    /// A 2D texture binding with unsigned integer values, for [`ColorFormat::R32UI`] textures
+    ///
+    /// Integer textures can only be sampled with `Nearest` filtering.
+    ///
+    /// [`ColorFormat::R32UI`]: crate::ColorFormat::R32UI
+    USampler2D,
+    /// A 2D texture binding with signed integer values, for [`ColorFormat::R32I`] textures
+    ///
+    /// Integer textures can only be sampled with `Nearest` filtering.
+    ///
+    /// [`ColorFormat::R32I`]: crate::ColorFormat::R32I
+    ISampler2D,
+    /// A depth texture binding that compares against a reference value, see
+    /// [`Sampler::set_compare_mode`]
+    ///
+    /// Depth textures can be bound to either this or a [`UniformType::Sampler2D`], which reads
+    /// the depth values directly.
+    ///
+    /// [`Sampler::set_compare_mode`]: crate::Sampler::set_compare_mode
+    Sampler2DShadow,
     /// An array of another type
     Array(Box<UniformType>, usize),
     /// A user-defined type, like a struct
@@ -41,22 +75,96 @@ pub enum UniformType {
 }
 
 impl UniformType {
-    fn write_type(&self, shader: &mut String) {
+    pub(crate) fn write_type(&self, shader: &mut String) {
+        shader.push_str(&self.glsl_name());
+        shader.push(' ');
+    }
+
+    /// The name of this type in GLSL, like `vec4` or `mat3`
+    pub(crate) fn glsl_name(&self) -> String {
         use NumberType::*;
         use UniformType::*;
 
         match self {
-            Scalar(Int) => shader.push_str("int "),
-            Scalar(Float) => shader.push_str("float "),
-            Vector(Int, x) => shader.push_str(&format!("ivec{} ", *x as i32)),
-            Vector(Float, x) => shader.push_str(&format!("vec{} ", *x as i32)),
-            Matrix(x) => shader.push_str(&format!("mat{} ", *x as i32)),
-            Sampler2D => shader.push_str("sampler2D "),
-            Array(u_type, dim) => {
-                u_type.write_type(shader);
-                shader.push_str(&format!("[{}]", dim));
-            }
-            UserType(string) => shader.push_str(&string),
+            Scalar(Int) => "int".to_owned(),
+            Scalar(Float) => "float".to_owned(),
+            Vector(Int, x) => format!("ivec{}", *x as i32),
+            Vector(Float, x) => format!("vec{}", *x as i32),
+            Matrix(x) => format!("mat{}", *x as i32),
+            Sampler2D => "sampler2D".to_owned(),
+            SamplerCube => "samplerCube".to_owned(),
+            Sampler2DArray => "sampler2DArray".to_owned(),
+            Sampler3D => "sampler3D".to_owned(),
+            USampler2D => "usampler2D".to_owned(),
+            ISampler2D => "isampler2D".to_owned(),
+            Sampler2DShadow => "sampler2DShadow".to_owned(),
+            Array(u_type, dim) => format!("{}[{}]", u_type.glsl_name(), dim),
+            UserType(string) => string.clone(),
+        }
+    }
+
+    /// Check that a value can be provided to a uniform of this type
+    ///
+    /// Arrays can be set with an array value of at most the declared length, or with a single
+    /// value which sets the first element. User-defined types can't be checked.
+    pub(crate) fn check_value(&self, name: &str, value: &UniformValue) -> Result<(), GolemError> {
+        use UniformType::*;
+
+        let matches = match (self, value.array_len()) {
+            (Array(u_type, length), Some(count)) => count <= *length && u_type.matches(value),
+            (Array(u_type, _), None) => u_type.matches(value),
+            (UserType(_), _) => true,
+            (u_type, None) => u_type.matches(value),
+            (_, Some(_)) => false,
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(GolemError::UniformTypeMismatch {
+                name: name.to_owned(),
+                expected: self.glsl_name(),
+                actual: value.glsl_name(),
+            })
+        }
+    }
+
+    /// If this is one of the texture sampler types
+    pub(crate) fn is_sampler(&self) -> bool {
+        use UniformType::*;
+        matches!(
+            self,
+            Sampler2D
+                | SamplerCube
+                | Sampler2DArray
+                | Sampler3D
+                | USampler2D
+                | ISampler2D
+                | Sampler2DShadow
+        )
+    }
+
+    /// If the elements of the value are this type
+    fn matches(&self, value: &UniformValue) -> bool {
+        use NumberType::*;
+        use UniformType::*;
+        use UniformValue as V;
+
+        match (self, value) {
+            (Scalar(Int), V::Int(_) | V::IntArray(_)) => true,
+            (Scalar(Float), V::Float(_) | V::FloatArray(_)) => true,
+            (Vector(Int, D2), V::IVector2(_) | V::IVector2Array(_)) => true,
+            (Vector(Int, D3), V::IVector3(_) | V::IVector3Array(_)) => true,
+            (Vector(Int, D4), V::IVector4(_) | V::IVector4Array(_)) => true,
+            (Vector(Float, D2), V::Vector2(_) | V::Vector2Array(_)) => true,
+            (Vector(Float, D3), V::Vector3(_) | V::Vector3Array(_)) => true,
+            (Vector(Float, D4), V::Vector4(_) | V::Vector4Array(_)) => true,
+            (Matrix(D2), V::Matrix2(_) | V::Matrix2Array(_)) => true,
+            (Matrix(D3), V::Matrix3(_) | V::Matrix3Array(_)) => true,
+            (Matrix(D4), V::Matrix4(_) | V::Matrix4Array(_)) => true,
+            // Samplers are set to the texture unit they read from
+            (u_type, V::Int(_) | V::IntArray(_)) => u_type.is_sampler(),
+            _ => false,
         }
     }
 }
@@ -66,8 +174,11 @@ impl UniformType {
 ///
 /// Use [`ShaderProgram::set_uniform`] to provide it.
 ///
+/// The array variants set several elements of a [`UniformType::Array`] at once, starting from
+/// the named element (the first one, unless the name is indexed like `lights[3]`).
+///
 /// [`ShaderProgram::set_uniform`]: crate::ShaderProgram::set_uniform
-pub enum UniformValue {
+pub enum UniformValue<'a> {
     Int(i32),
     Float(f32),
     Vector2([f32; 2]),
@@ -79,4 +190,60 @@ pub enum UniformValue {
     Matrix2([f32; 4]),
     Matrix3([f32; 9]),
     Matrix4([f32; 16]),
+    IntArray(&'a [i32]),
+    FloatArray(&'a [f32]),
+    Vector2Array(&'a [[f32; 2]]),
+    Vector3Array(&'a [[f32; 3]]),
+    Vector4Array(&'a [[f32; 4]]),
+    IVector2Array(&'a [[i32; 2]]),
+    IVector3Array(&'a [[i32; 3]]),
+    IVector4Array(&'a [[i32; 4]]),
+    Matrix2Array(&'a [[f32; 4]]),
+    Matrix3Array(&'a [[f32; 9]]),
+    Matrix4Array(&'a [[f32; 16]]),
+}
+
+impl UniformValue<'_> {
+    /// The number of elements, if this is an array value
+    pub(crate) fn array_len(&self) -> Option<usize> {
+        use UniformValue::*;
+
+        match self {
+            IntArray(x) => Some(x.len()),
+            FloatArray(x) => Some(x.len()),
+            Vector2Array(x) => Some(x.len()),
+            Vector3Array(x) => Some(x.len()),
+            Vector4Array(x) => Some(x.len()),
+            IVector2Array(x) => Some(x.len()),
+            IVector3Array(x) => Some(x.len()),
+            IVector4Array(x) => Some(x.len()),
+            Matrix2Array(x) => Some(x.len()),
+            Matrix3Array(x) => Some(x.len()),
+            Matrix4Array(x) => Some(x.len()),
+            _ => None,
+        }
+    }
+
+    /// The name of this value's type in GLSL, like `vec4` or `mat3[2]`
+    pub(crate) fn glsl_name(&self) -> String {
+        use UniformValue::*;
+
+        let element = match self {
+            Int(_) | IntArray(_) => "int",
+            Float(_) | FloatArray(_) => "float",
+            Vector2(_) | Vector2Array(_) => "vec2",
+            Vector3(_) | Vector3Array(_) => "vec3",
+            Vector4(_) | Vector4Array(_) => "vec4",
+            IVector2(_) | IVector2Array(_) => "ivec2",
+            IVector3(_) | IVector3Array(_) => "ivec3",
+            IVector4(_) | IVector4Array(_) => "ivec4",
+            Matrix2(_) | Matrix2Array(_) => "mat2",
+            Matrix3(_) | Matrix3Array(_) => "mat3",
+            Matrix4(_) | Matrix4Array(_) => "mat4",
+        };
+        match self.array_len() {
+            Some(length) => format!("{}[{}]", element, length),
+            None => element.to_owned(),
+        }
+    }
 }
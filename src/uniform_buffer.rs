@@ -0,0 +1,320 @@
+use crate::*;
+use std::borrow::ToOwned;
+use std::string::String;
+use std::vec::Vec;
+
+/// A named group of uniforms, backed by a [`UniformBuffer`] instead of individual values
+///
+/// Uniform blocks are declared in the [`ShaderDescription`], and every [`ShaderProgram`] that
+/// declares a block with the same name reads from the same [`UniformBuffer`]. This makes them
+/// ideal for values shared across many programs, like camera or lighting data.
+///
+/// The members are laid out in memory following the GLSL `std140` rules, see
+/// [`UniformBlock::layout`]. Samplers and user-defined types cannot be members of a block.
+///
+/// [`ShaderDescription`]: crate::ShaderDescription
+/// [`ShaderProgram`]: crate::ShaderProgram
+pub struct UniformBlock<'a> {
+    pub name: &'static str,
+    pub members: &'a [Uniform],
+}
+
+impl<'a> UniformBlock<'a> {
+    pub fn new(name: &'static str, members: &'a [Uniform]) -> UniformBlock<'a> {
+        UniformBlock { name, members }
+    }
+
+    pub(crate) fn as_glsl(&self, shader: &mut String) {
+        shader.push_str("layout(std140) uniform ");
+        shader.push_str(self.name);
+        shader.push('{');
+        for member in self.members.iter() {
+            member.u_type.write_type(shader);
+            shader.push_str(member.name);
+            shader.push(';');
+        }
+        shader.push_str("};");
+    }
+
+    /// Compute where each member of the block is stored, following the GLSL `std140` rules
+    pub fn layout(&self) -> Result<UniformBlockLayout, GolemError> {
+        let mut size = 0;
+        let mut members = Vec::with_capacity(self.members.len());
+        for member in self.members.iter() {
+            let (alignment, member_size) = std140_layout(&member.u_type)
+                .ok_or_else(|| GolemError::IllegalUniformBlockMember(member.name.to_owned()))?;
+            let offset = align_to(size, alignment);
+            members.push(LayoutMember {
+                name: member.name,
+                u_type: member.u_type.clone(),
+                offset,
+            });
+            size = offset + member_size;
+        }
+
+        Ok(UniformBlockLayout {
+            size: align_to(size, 16),
+            members,
+        })
+    }
+}
+
+/// The `std140` memory layout of a [`UniformBlock`]
+pub struct UniformBlockLayout {
+    size: usize,
+    members: Vec<LayoutMember>,
+}
+
+struct LayoutMember {
+    name: &'static str,
+    u_type: UniformType,
+    offset: usize,
+}
+
+impl UniformBlockLayout {
+    /// The size of the whole block in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The offset in bytes of the member with the given name, if there is one
+    pub fn offset(&self, name: &str) -> Option<usize> {
+        self.member(name).map(|member| member.offset)
+    }
+
+    fn member(&self, name: &str) -> Option<&LayoutMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+}
+
+fn align_to(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
+}
+
+/// The alignment and size in bytes of a `std140` uniform, if it can be stored in a block
+fn std140_layout(u_type: &UniformType) -> Option<(usize, usize)> {
+    use UniformType::*;
+
+    match u_type {
+        Scalar(_) => Some((4, 4)),
+        Vector(_, Dimension::D2) => Some((8, 8)),
+        Vector(_, Dimension::D3) => Some((16, 12)),
+        Vector(_, Dimension::D4) => Some((16, 16)),
+        // Matrices are stored as arrays of column vectors, each padded to a vec4
+        Matrix(n) => Some((16, 16 * *n as usize)),
+        // Array elements are each padded to a vec4
+        Array(u_type, length) => {
+            let (_, size) = std140_layout(u_type)?;
+            Some((16, align_to(size, 16) * length))
+        }
+        Sampler2D | SamplerCube | Sampler2DArray | Sampler3D | USampler2D | ISampler2D
+        | Sampler2DShadow => None,
+        UserType(_) => None,
+    }
+}
+
+/// Write a value in its `std140` representation
+fn write_std140(value: &UniformValue, out: &mut Vec<u8>) {
+    use UniformValue::*;
+
+    // Write the values in pieces of `chunk` components, each padded out to `stride` bytes
+    fn write<T: bytemuck::Pod>(out: &mut Vec<u8>, values: &[T], chunk: usize, stride: usize) {
+        for piece in values.chunks(chunk) {
+            let bytes: &[u8] = bytemuck::cast_slice(piece);
+            out.extend_from_slice(bytes);
+            out.resize(out.len() + stride - bytes.len(), 0);
+        }
+    }
+
+    // Matrices are written as columns padded to a vec4, and array elements are padded to a vec4
+    match value {
+        Int(x) => write(out, &[*x], 1, 4),
+        IVector2(x) => write(out, x, 2, 8),
+        IVector3(x) => write(out, x, 3, 12),
+        IVector4(x) => write(out, x, 4, 16),
+        Float(x) => write(out, &[*x], 1, 4),
+        Vector2(x) => write(out, x, 2, 8),
+        Vector3(x) => write(out, x, 3, 12),
+        Vector4(x) => write(out, x, 4, 16),
+        Matrix2(mat) => write(out, mat, 2, 16),
+        Matrix3(mat) => write(out, mat, 3, 16),
+        Matrix4(mat) => write(out, mat, 4, 16),
+        IntArray(x) => write(out, x, 1, 16),
+        IVector2Array(x) => write(out, bytemuck::cast_slice::<_, i32>(x), 2, 16),
+        IVector3Array(x) => write(out, bytemuck::cast_slice::<_, i32>(x), 3, 16),
+        IVector4Array(x) => write(out, bytemuck::cast_slice::<_, i32>(x), 4, 16),
+        FloatArray(x) => write(out, x, 1, 16),
+        Vector2Array(x) => write(out, bytemuck::cast_slice::<_, f32>(x), 2, 16),
+        Vector3Array(x) => write(out, bytemuck::cast_slice::<_, f32>(x), 3, 16),
+        Vector4Array(x) => write(out, bytemuck::cast_slice::<_, f32>(x), 4, 16),
+        Matrix2Array(mat) => write(out, bytemuck::cast_slice::<_, f32>(mat), 2, 16),
+        Matrix3Array(mat) => write(out, bytemuck::cast_slice::<_, f32>(mat), 3, 16),
+        Matrix4Array(mat) => write(out, bytemuck::cast_slice::<_, f32>(mat), 4, 16),
+    }
+}
+
+/// A buffer on the GPU that holds the values of a [`UniformBlock`]
+///
+/// Once bound with [`UniformBuffer::bind`], every [`ShaderProgram`] that declares a block with the
+/// same name will read its values from this buffer.
+///
+/// ```rust
+/// # use golem::*;
+/// # use golem::Dimension::*;
+/// # fn func(ctx: &Context) -> Result<(), GolemError> {
+/// let members = [
+///     Uniform::new("projection", UniformType::Matrix(D4)),
+///     Uniform::new("view", UniformType::Matrix(D4)),
+/// ];
+/// let camera = UniformBlock::new("Camera", &members);
+/// let buffer = UniformBuffer::new(ctx, &camera)?;
+/// buffer.set_uniform("view", UniformValue::Matrix4([0.0; 16]))?;
+/// buffer.bind();
+/// # Ok(()) }
+/// ```
+///
+/// [`ShaderProgram`]: crate::ShaderProgram
+pub struct UniformBuffer {
+    ctx: Context,
+    buffer: Buffer<u8>,
+    binding: u32,
+    layout: UniformBlockLayout,
+}
+
+impl UniformBuffer {
+    /// Create a buffer to hold the values of the given block
+    ///
+    /// The values all start as zero.
+    pub fn new(ctx: &Context, block: &UniformBlock) -> Result<UniformBuffer, GolemError> {
+        let layout = block.layout()?;
+        let binding = ctx.uniform_block_binding(block.name)?;
+        let mut buffer = Buffer::with_target(ctx, glow::UNIFORM_BUFFER)?;
+        buffer.set_data(&vec![0; layout.size()]);
+
+        Ok(UniformBuffer {
+            ctx: Context(ctx.0.clone()),
+            buffer,
+            binding,
+            layout,
+        })
+    }
+
+    /// The memory layout of the block this buffer holds
+    pub fn layout(&self) -> &UniformBlockLayout {
+        &self.layout
+    }
+
+    /// Set the value of one member of the block
+    ///
+    /// The value is checked against the member's declared [`UniformType`], returning
+    /// [`GolemError::UniformTypeMismatch`] if they disagree.
+    pub fn set_uniform(&self, name: &str, uniform: UniformValue) -> Result<(), GolemError> {
+        let member = self
+            .layout
+            .member(name)
+            .ok_or_else(|| GolemError::NoSuchUniform(name.to_owned()))?;
+        member.u_type.check_value(name, &uniform)?;
+        let mut data = Vec::new();
+        write_std140(&uniform, &mut data);
+        self.buffer.bind();
+        self.buffer.set_sub_data(member.offset, &data);
+
+        Ok(())
+    }
+
+    /// Bind this buffer to the block, so shader programs will read their values from it
+    ///
+    /// The buffer stays bound until another buffer for the same block is bound.
+    pub fn bind(&self) {
+        // https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBindBufferBase.xhtml
+        // The binding was allocated by the context, so it's below MAX_UNIFORM_BUFFER_BINDINGS
+        unsafe {
+            self.ctx.0.gl.bind_buffer_base(
+                glow::UNIFORM_BUFFER,
+                self.binding,
+                Some(self.buffer.id),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dimension::*;
+    use crate::NumberType::*;
+    use std::boxed::Box;
+
+    fn layout(members: &[Uniform]) -> UniformBlockLayout {
+        UniformBlock::new("Block", members)
+            .layout()
+            .expect("The members are all allowed in a block")
+    }
+
+    #[test]
+    fn vec3_is_aligned_to_a_vec4() {
+        let layout = layout(&[
+            Uniform::new("a", UniformType::Scalar(Float)),
+            Uniform::new("b", UniformType::Vector(Float, D3)),
+            Uniform::new("c", UniformType::Scalar(Float)),
+        ]);
+        assert_eq!(layout.offset("a"), Some(0));
+        assert_eq!(layout.offset("b"), Some(16));
+        // A scalar fits in the padding after a vec3
+        assert_eq!(layout.offset("c"), Some(28));
+        assert_eq!(layout.size(), 32);
+    }
+
+    #[test]
+    fn matrices_are_stored_as_padded_columns() {
+        let layout = layout(&[
+            Uniform::new("a", UniformType::Vector(Float, D2)),
+            Uniform::new("b", UniformType::Matrix(D4)),
+            Uniform::new("c", UniformType::Matrix(D3)),
+            Uniform::new("d", UniformType::Scalar(Int)),
+        ]);
+        assert_eq!(layout.offset("b"), Some(16));
+        assert_eq!(layout.offset("c"), Some(80));
+        assert_eq!(layout.offset("d"), Some(128));
+        assert_eq!(layout.size(), 144);
+
+        let mut data = Vec::new();
+        write_std140(&UniformValue::Matrix3([1.0; 9]), &mut data);
+        assert_eq!(data.len(), 48);
+        let columns: &[f32] = bytemuck::cast_slice(&data);
+        assert_eq!(&columns[..4], &[1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn array_elements_are_padded_to_a_vec4() {
+        let layout = layout(&[
+            Uniform::new(
+                "a",
+                UniformType::Array(Box::new(UniformType::Scalar(Float)), 3),
+            ),
+            Uniform::new(
+                "b",
+                UniformType::Array(Box::new(UniformType::Vector(Float, D3)), 2),
+            ),
+            Uniform::new("c", UniformType::Scalar(Float)),
+        ]);
+        assert_eq!(layout.offset("a"), Some(0));
+        assert_eq!(layout.offset("b"), Some(48));
+        assert_eq!(layout.offset("c"), Some(80));
+        assert_eq!(layout.size(), 96);
+
+        let mut data = Vec::new();
+        write_std140(&UniformValue::FloatArray(&[1.0, 2.0]), &mut data);
+        let values: &[f32] = bytemuck::cast_slice(&data);
+        assert_eq!(values, &[1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn samplers_are_not_allowed() {
+        let members = [Uniform::new("tex", UniformType::Sampler2D)];
+        match UniformBlock::new("Block", &members).layout() {
+            Err(GolemError::IllegalUniformBlockMember(name)) => assert_eq!(name, "tex"),
+            _ => panic!("Samplers can't be stored in a uniform block"),
+        }
+    }
+}